@@ -0,0 +1,38 @@
+//!A plugin that responds with the request body, also used by the stdio transport tests.
+//! Run it with `tcp 127.0.0.1:8080`, `unix /tmp/echo.sock` or `stdio` to pick how RAPID reaches it
+
+use rapid_utils::err::HttpError;
+use rapid_utils::plugin::{InputSequence, PluginResponse};
+#[cfg(unix)]
+use rapid_utils::sdk::serve_unix;
+use rapid_utils::sdk::{serve, serve_stdio, RapidPlugin};
+
+struct Echo;
+
+#[tonic::async_trait]
+impl RapidPlugin for Echo {
+    async fn handle(&self, request: InputSequence) -> Result<PluginResponse, HttpError> {
+        Ok(PluginResponse {
+            status: 200,
+            headers: request.headers,
+            body: request.body,
+        })
+    }
+}
+
+//built by hand, the crate doesn't enable tokio's macros outside of tests
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(run())
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["tcp", addr] => serve(Echo, addr.parse()?).await?,
+        #[cfg(unix)]
+        ["unix", path] => serve_unix(Echo, path).await?,
+        ["stdio"] => serve_stdio(Echo).await?,
+        _ => eprintln!("usage: echo-plugin tcp <addr> | unix <path> | stdio"),
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//!Prints the catalogue of every RAPID error code.
+//! Usage: error-catalogue [json|markdown], defaults to json
+use std::env;
+use std::process::exit;
+
+use rapid_utils::catalogue::{catalogue_json, catalogue_markdown};
+
+fn main() {
+    match env::args().nth(1).as_deref() {
+        None | Some("json") => println!("{}", catalogue_json()),
+        Some("markdown") | Some("md") => print!("{}", catalogue_markdown()),
+        Some(other) => {
+            eprintln!("Unknown format {}, expected json or markdown", other);
+            exit(1);
+        }
+    }
+}
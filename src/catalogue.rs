@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::err::{ErrorCategory, ErrorCode, RetryHint};
+use crate::registry::registered_codes;
+
+///One documented error code
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueEntry {
+    pub name: String,
+    pub http_status: u16,
+    pub category: ErrorCategory,
+    pub retry: RetryHint,
+    ///Only set if retry is retry_after
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u32>,
+    pub description: String,
+}
+
+impl From<&ErrorCode> for CatalogueEntry {
+    fn from(code: &ErrorCode) -> Self {
+        CatalogueEntry {
+            name: code.name().to_string(),
+            http_status: code.http_status.as_u16(),
+            category: code.category(),
+            retry: code.retry(),
+            retry_after: code.retry().retry_after(),
+            description: code.description.unwrap_or_default().to_string(),
+        }
+    }
+}
+
+///Every code in the [crate::registry], ordered by name
+pub fn catalogue() -> Vec<CatalogueEntry> {
+    registered_codes().iter().map(CatalogueEntry::from).collect()
+}
+
+pub fn catalogue_json() -> String {
+    serde_json::to_string_pretty(&catalogue()).unwrap_or_else(|_| "[]".to_string())
+}
+
+///A Markdown table of the catalogue, for the public API docs
+pub fn catalogue_markdown() -> String {
+    let mut md = String::from("| Code | HTTP status | Category | Retry | Description |\n|---|---|---|---|---|\n");
+    for entry in catalogue() {
+        let retry = match entry.retry_after {
+            Some(secs) => format!("after {}s", secs),
+            None => entry.retry.as_str().to_string(),
+        };
+        md.push_str(
+            format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                entry.name,
+                entry.http_status,
+                entry.category,
+                retry,
+                entry.description.replace('|', "\\|")
+            )
+            .as_str(),
+        );
+    }
+    md
+}
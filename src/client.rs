@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::warn;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+use tonic::{Status, Streaming};
+
+use crate::err::PipelineError;
+use crate::plugin::rapid_plugin_client::RapidPluginClient;
+use crate::plugin::{output_sequence, InputSequence, OutputSequence, PluginResponse};
+use crate::transport::{self, PluginAddr};
+
+///How many requests can be queued for the plugin before callers wait for the stream to catch up
+const INPUT_BUFFER: usize = 64;
+
+///Called with responses the client can't route back to a caller
+pub type ErrorHandler = Arc<dyn Fn(PipelineError) + Send + Sync>;
+
+type Waiter = oneshot::Sender<Result<PluginResponse, PipelineError>>;
+//None once the plugin's side of the stream has ended
+type Pending = Arc<Mutex<Option<HashMap<u64, Waiter>>>>;
+
+///Host side of the plugin protocol.
+/// Multiplexes requests over a single Execute stream, giving each one a unique sequence id and
+/// routing the plugin's response back to the caller waiting on it
+pub struct PluginClient {
+    name: String,
+    next_id: Arc<AtomicU64>,
+    inputs: mpsc::Sender<InputSequence>,
+    pending: Pending,
+}
+
+impl PluginClient {
+    ///Connects to the plugin at `dst` e.g. `http://10.0.0.2:8080` or `unix:///run/plugin.sock`, see [PluginAddr] for the supported URLs.
+    /// Failing to connect is a [PipelineError::PluginTransportErr]
+    pub async fn connect(name: &str, dst: &str) -> Result<Self, PipelineError> {
+        let client = transport::connect(&dst.parse::<PluginAddr>()?).await?;
+        PluginClient::new(name, client).await
+    }
+
+    ///Opens the Execute stream, responses that can't be routed to a caller are logged
+    pub async fn new(name: &str, client: RapidPluginClient<Channel>) -> Result<Self, PipelineError> {
+        let handler: ErrorHandler = Arc::new(|e| warn!("Unroutable plugin response. {}", e));
+        PluginClient::with_error_handler(name, client, handler).await
+    }
+
+    ///Same as [PluginClient::new] but responses that can't be routed to a caller are passed to `on_error`.
+    /// These are [PipelineError::DuplicateSequenceId] if the plugin answers the same request twice, or answers after its
+    /// caller stopped waiting, or [PipelineError::PluginErr] if it answers a request that was never sent
+    pub async fn with_error_handler(
+        name: &str,
+        mut client: RapidPluginClient<Channel>,
+        on_error: ErrorHandler,
+    ) -> Result<Self, PipelineError> {
+        let (inputs, rx) = mpsc::channel(INPUT_BUFFER);
+        let outputs = client
+            .execute(ReceiverStream::new(rx))
+            .await
+            .map_err(PipelineError::PluginStatusErr)?
+            .into_inner();
+        let client = PluginClient {
+            name: name.to_string(),
+            //0 is the id of sequences nobody has set
+            next_id: Arc::new(AtomicU64::new(1)),
+            inputs,
+            pending: Arc::new(Mutex::new(Some(HashMap::new()))),
+        };
+        tokio::spawn(route(
+            client.name.clone(),
+            outputs,
+            client.pending.clone(),
+            client.next_id.clone(),
+            on_error,
+        ));
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    ///Sends the request to the plugin and waits for its response, any id already on the request is replaced.
+    /// An error from the plugin is returned as [PipelineError::PluginSeqErr]
+    pub async fn execute(&self, mut request: InputSequence) -> Result<PluginResponse, PipelineError> {
+        let id = self.allocate_id();
+        request.id = id;
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = lock(&self.pending);
+            let pending = pending.as_mut().ok_or_else(|| closed(&self.name))?;
+            //only possible if ids wrap around while a request is still waiting
+            if pending.contains_key(&id) {
+                return Err(PipelineError::DuplicateSequenceId);
+            }
+            pending.insert(id, tx);
+        }
+        //removes the waiter if the caller gives up e.g. times out while the input buffer is full
+        let _waiting = Waiting { pending: &self.pending, id };
+        if let Err(e) = self.inputs.send(request).await {
+            return Err(PipelineError::PluginChannelErr(e));
+        }
+        match rx.await {
+            Ok(result) => result,
+            Err(_) => Err(closed(&self.name)),
+        }
+    }
+
+    ///Requests sent to the plugin that are still waiting for a response
+    pub fn in_flight(&self) -> usize {
+        lock(&self.pending).as_ref().map_or(0, HashMap::len)
+    }
+
+    fn allocate_id(&self) -> u64 {
+        match self.next_id.fetch_add(1, Ordering::Relaxed) {
+            0 => self.next_id.fetch_add(1, Ordering::Relaxed),
+            id => id,
+        }
+    }
+}
+
+///Removes a request's waiter when [PluginClient::execute] returns or is dropped, a no-op once the response was routed
+struct Waiting<'a> {
+    pending: &'a Pending,
+    id: u64,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(pending) = lock(self.pending).as_mut() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+///Reads the plugin's responses until the stream ends then fails every request still waiting
+async fn route(
+    name: String,
+    mut outputs: Streaming<OutputSequence>,
+    pending: Pending,
+    next_id: Arc<AtomicU64>,
+    on_error: ErrorHandler,
+) {
+    let failure: Option<Status> = loop {
+        let output = match outputs.next().await {
+            Some(Ok(output)) => output,
+            Some(Err(status)) => break Some(status),
+            None => break None,
+        };
+        let id = output.id;
+        let waiter = lock(&pending).as_mut().and_then(|p| p.remove(&id));
+        match waiter {
+            Some(waiter) => {
+                let result = match output.value {
+                    Some(output_sequence::Value::Response(response)) => Ok(response),
+                    Some(output_sequence::Value::Error(e)) => Err(PipelineError::PluginSeqErr(e)),
+                    None => Err(PipelineError::PluginErr(
+                        format!("Empty response for sequence id {}.", id),
+                        name.clone(),
+                    )),
+                };
+                //the caller may have given up, that's not the plugin's fault
+                let _ = waiter.send(result);
+            }
+            None if id != 0 && id < next_id.load(Ordering::Relaxed) => on_error(PipelineError::DuplicateSequenceId),
+            None => on_error(PipelineError::PluginErr(
+                format!("Response for unknown sequence id {}.", id),
+                name.clone(),
+            )),
+        }
+    };
+    let waiters = lock(&pending).take().unwrap_or_default();
+    for waiter in waiters.into_values() {
+        let err = match &failure {
+            Some(status) => PipelineError::PluginStatusErr(status.clone()),
+            None => closed(&name),
+        };
+        let _ = waiter.send(Err(err));
+    }
+}
+
+fn closed(name: &str) -> PipelineError {
+    PipelineError::PluginErr("Plugin closed the stream.".to_string(), name.to_string())
+}
+
+fn lock(pending: &Pending) -> MutexGuard<'_, Option<HashMap<u64, Waiter>>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
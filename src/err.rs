@@ -1,32 +1,204 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use bytes::Bytes;
 use http::StatusCode;
 use log::{info, warn};
 use rapid_fs::vfs::VfsErr;
+use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
-use serde_json::json;
+use serde_json::{json, Value};
 use thiserror::Error;
 
 use crate::hypi_rapid_plugin::{InputSequence, output_sequence, Pair, PluginError};
 use crate::plugin::OutputSequence;
+use crate::transport::TransportError;
 // use crate::plugin::hypi_rapid_plugin::{InputSequence, PluginError};
-use crate::wellknown::CODE_PIPELINE_PLUGIN_CONN_ERR;
+use crate::wellknown::{
+    CODE_DOCKER_ERR, CODE_FORM_FILE_ERR, CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED, CODE_FS_DOMAIN,
+    CODE_FS_DOT_PATHS_NOT_SUPPORTED, CODE_FS_FILE_NOT_FOUND, CODE_FS_IO_ERROR, CODE_FS_SCHEMA_FILE_NOT_FOUND,
+    CODE_INVALID_UTF8, CODE_JSON_ERR, CODE_PIPELINE_DUPLICATE_SEQ_ID, CODE_PIPELINE_EMPTY,
+    CODE_PIPELINE_PLUGIN_BEHAVIOUR, CODE_PIPELINE_PLUGIN_CONN, CODE_PIPELINE_PLUGIN_INVALID_STATUS, CODE_RAPID_SCRIPT_ERR, CODE_RAPID_SCRIPT_SYNTAX_ERR,
+    CODE_SCRIPT_ERR, CODE_UNSUPPORTED_PROTOCOL, CONTEXT_JSON_PREFIX,
+};
 
+///Well known codes are `const` and borrow their name, so cloning them doesn't allocate.
+/// `Cow` isn't structural so the consts can't be used as patterns, match [ErrorCode::name] against [crate::wellknown::names] instead.
+/// Two codes are equal if their name and status are, the description, category and retry hint are metadata
 #[derive(Debug, Clone)]
 pub struct ErrorCode {
-    pub name: String,
+    pub name: Cow<'static, str>,
     pub http_status: StatusCode,
+    ///What the error means, used to generate the error catalogue
+    pub description: Option<&'static str>,
+    ///Derived from the HTTP status if not set, see [ErrorCode::category]
+    pub category: Option<ErrorCategory>,
+    ///Derived from the HTTP status if not set, see [ErrorCode::retry]
+    pub retry: Option<RetryHint>,
+}
+
+///Where an error comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+    ///The request was invalid, sending it again won't help
+    Client,
+    ///RAPID itself failed
+    Server,
+    ///Something RAPID depends on failed e.g. a database or Docker
+    Dependency,
+    ///A plugin failed or misbehaved
+    Plugin,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Client => "client",
+            ErrorCategory::Server => "server",
+            ErrorCategory::Dependency => "dependency",
+            ErrorCategory::Plugin => "plugin",
+        }
+    }
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+///Whether the request that caused an error can be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryHint {
+    ///The error is likely transient, retrying with backoff may succeed
+    Retryable,
+    ///Retry but not before this many seconds
+    RetryAfter(u32),
+    ///Retrying the same request will fail the same way
+    Fatal,
+}
+
+impl RetryHint {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, RetryHint::Fatal)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryHint::Retryable => "retryable",
+            RetryHint::RetryAfter(_) => "retry_after",
+            RetryHint::Fatal => "fatal",
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<u32> {
+        match self {
+            RetryHint::RetryAfter(secs) => Some(*secs),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for RetryHint {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
 impl ErrorCode {
     pub fn new(name: &str, status: StatusCode) -> Self {
         Self {
-            name: name.to_string(),
+            name: Cow::Owned(name.to_string()),
+            http_status: status,
+            description: None,
+            category: None,
+            retry: None,
+        }
+    }
+
+    ///For codes known at compile time e.g. `pub const CODE_X: ErrorCode = ErrorCode::of("x", StatusCode::BAD_REQUEST);`
+    pub const fn of(name: &'static str, status: StatusCode) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            http_status: status,
+            description: None,
+            category: None,
+            retry: None,
+        }
+    }
+
+    ///Same as [ErrorCode::of] but with a description for the error catalogue
+    pub const fn described(name: &'static str, status: StatusCode, description: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
             http_status: status,
+            description: Some(description),
+            category: None,
+            retry: None,
         }
     }
+
+    ///Same as [ErrorCode::described] but with a category and retry hint instead of ones derived from the status
+    pub const fn classified(
+        name: &'static str,
+        status: StatusCode,
+        description: &'static str,
+        category: ErrorCategory,
+        retry: RetryHint,
+    ) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            http_status: status,
+            description: Some(description),
+            category: Some(category),
+            retry: Some(retry),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    ///The category set on the code, otherwise 502, 503 and 504 are [ErrorCategory::Dependency],
+    /// other 4xx are [ErrorCategory::Client] and anything else [ErrorCategory::Server]
+    pub fn category(&self) -> ErrorCategory {
+        self.category.unwrap_or(match self.http_status.as_u16() {
+            502..=504 => ErrorCategory::Dependency,
+            400..=499 => ErrorCategory::Client,
+            _ => ErrorCategory::Server,
+        })
+    }
+
+    ///The retry hint set on the code, otherwise 408, 429, 502, 503 and 504 are [RetryHint::Retryable]
+    /// and anything else is [RetryHint::Fatal]
+    pub fn retry(&self) -> RetryHint {
+        self.retry.unwrap_or(match self.http_status.as_u16() {
+            408 | 429 | 502..=504 => RetryHint::Retryable,
+            _ => RetryHint::Fatal,
+        })
+    }
+}
+
+impl PartialEq for ErrorCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.http_status == other.http_status
+    }
+}
+
+impl Eq for ErrorCode {}
+
+impl Hash for ErrorCode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.http_status.hash(state);
+    }
 }
 
 impl Serialize for ErrorCode {
@@ -34,23 +206,153 @@ impl Serialize for ErrorCode {
         where
             S: Serializer,
     {
-        serializer.serialize_str(self.name.as_str())
+        serializer.serialize_str(self.name())
     }
 }
 
 impl Display for ErrorCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.name.as_str())
+        f.write_str(self.name())
     }
 }
 
-#[derive(Debug, Serialize)]
+///Extra information about an error, values can be any JSON type e.g. numbers, lists or nested objects.
+/// Plain string values serialise exactly as the old string only context did.
+pub type ErrorContext = HashMap<String, Value>;
+
+///Any error that can be kept as the cause of a HttpError
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync + 'static>;
+
+///Serialises as `{code, message, context, category, retry}` plus `retry_after` (seconds) if the code has one
+#[derive(Debug)]
 pub struct HttpError {
     pub code: ErrorCode,
     pub message: String,
-    pub context: Option<HashMap<String, String>>,
+    pub context: Option<ErrorContext>,
+    ///The underlying error, if any. It is only for logs and is never serialised into the response
+    pub source: Option<ErrorSource>,
+    ///Set on copies made by [crate::redact::RedactionPolicy::redact] so redacting again keeps the (possibly localised) message
+    pub(crate) message_redacted: bool,
+}
+
+impl Serialize for HttpError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        let retry = self.code.retry();
+        let mut s = serializer.serialize_struct("HttpError", if retry.retry_after().is_some() { 6 } else { 5 })?;
+        s.serialize_field("code", &self.code)?;
+        s.serialize_field("message", &self.message)?;
+        s.serialize_field("context", &self.context)?;
+        s.serialize_field("category", &self.code.category())?;
+        s.serialize_field("retry", &retry)?;
+        if let Some(secs) = retry.retry_after() {
+            s.serialize_field("retry_after", &secs)?;
+        }
+        s.end()
+    }
+}
+
+impl HttpError {
+    ///Start building an error with the given code e.g. `HttpError::new(&CODE_X).message("...").with("key", 1).build()`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(code: &ErrorCode) -> HttpErrorBuilder {
+        HttpErrorBuilder {
+            code: code.clone(),
+            message: String::new(),
+            context: None,
+            source: None,
+        }
+    }
+
+    ///Displays the error followed by every error in its source chain, intended for logs
+    /// e.g. `warn!("{}", err.chain())`
+    pub fn chain(&self) -> ErrorChain<'_> {
+        ErrorChain(self)
+    }
+
+    ///Adds (or replaces) a context entry and returns the error
+    pub fn with_context<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.insert_context(key, value);
+        self
+    }
+
+    ///Adds (or replaces) a context entry, returning the previous value if there was one
+    pub fn insert_context<K: Into<String>, V: Into<Value>>(&mut self, key: K, value: V) -> Option<Value> {
+        self.context
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into())
+    }
+
+    pub fn context_value(&self, key: &str) -> Option<&Value> {
+        self.context.as_ref().and_then(|c| c.get(key))
+    }
+
+    ///The context value for the key if it is a string
+    pub fn context_str(&self, key: &str) -> Option<&str> {
+        self.context_value(key).and_then(|v| v.as_str())
+    }
+}
+
+pub struct HttpErrorBuilder {
+    code: ErrorCode,
+    message: String,
+    context: Option<ErrorContext>,
+    source: Option<ErrorSource>,
+}
+
+impl HttpErrorBuilder {
+    pub fn message<M: Into<String>>(mut self, message: M) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    ///Sets the message from format args e.g. `.message_fmt(format_args!("Missing {}", name))`
+    pub fn message_fmt(mut self, args: std::fmt::Arguments<'_>) -> Self {
+        self.message = args.to_string();
+        self
+    }
+
+    ///Adds (or replaces) a context entry
+    pub fn with<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.context
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    ///Adds all the entries to the context, replacing any existing entry with the same key
+    pub fn with_all<I: IntoIterator<Item = (String, Value)>>(mut self, entries: I) -> Self {
+        self.context.get_or_insert_with(HashMap::new).extend(entries);
+        self
+    }
+
+    ///Keeps the error as the cause of this one, see [HttpError::chain]
+    pub fn source<E: std::error::Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    pub fn build(self) -> HttpError {
+        HttpError {
+            code: self.code,
+            message: self.message,
+            context: self.context.filter(|c| !c.is_empty()),
+            source: self.source,
+            message_redacted: false,
+        }
+    }
+}
+
+impl From<HttpErrorBuilder> for HttpError {
+    fn from(value: HttpErrorBuilder) -> Self {
+        value.build()
+    }
 }
 
+///The full error as JSON, including anything a [crate::redact::RedactionPolicy] would hide.
+/// Use `Bytes::from(&err)` for response bodies
 impl Display for HttpError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let json = serde_json::to_string(self).unwrap_or_else(|e| {
@@ -65,7 +367,25 @@ impl Display for HttpError {
     }
 }
 
-impl std::error::Error for HttpError {}
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|e| e.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+pub struct ErrorChain<'a>(&'a HttpError);
+
+impl Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.0.code, self.0.message)?;
+        let mut source = std::error::Error::source(self.0);
+        while let Some(e) = source {
+            write!(f, " Caused by: {}", e)?;
+            source = e.source();
+        }
+        Ok(())
+    }
+}
 
 // impl<T> ToOwned for &HttpError<T> where T:Serialize{
 //     type Owned = HttpError<T>;
@@ -86,12 +406,16 @@ impl From<&HttpError> for HttpError {
             code: value.code.clone(),
             message: value.message.to_owned(),
             context: value.context.to_owned(),
+            source: value.source.clone(),
+            message_redacted: value.message_redacted,
         }
     }
 }
 
+///The response body for the error, anything the current [crate::redact::RedactionPolicy] hides is left out
 impl From<&HttpError> for Bytes {
     fn from(value: &HttpError) -> Self {
+        let value = &value.redacted();
         serde_json::to_string(value)
             .unwrap_or_else(|_| {
                 info!(
@@ -114,52 +438,19 @@ impl From<&HttpError> for Bytes {
 impl From<VfsErr> for HttpError {
     fn from(value: VfsErr) -> Self {
         match value {
-            VfsErr::Domain(msg) => HttpError {
-                code: crate::wellknown::CODE_FS_DOMAIN.to_owned(),
-                message: msg,
-                context: None,
-            },
-            VfsErr::FileNotFound(msg) => HttpError {
-                code: crate::wellknown::CODE_FS_FILE_NOT_FOUND.to_owned(),
-                message: msg,
-                context: None,
-            },
-            VfsErr::SchemaFileNotFound(msg) => HttpError {
-                code: crate::wellknown::CODE_FS_SCHEMA_FILE_NOT_FOUND.to_owned(),
-                message: msg,
-                context: None,
-            },
-            VfsErr::AbsolutePathNotSupported(e) => HttpError {
-                code: crate::wellknown::CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            VfsErr::DotPathsNotSupported(e) => HttpError {
-                code: crate::wellknown::CODE_FS_DOT_PATHS_NOT_SUPPORTED.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            VfsErr::JsonErr(e) => HttpError {
-                code: crate::wellknown::CODE_JSON_ERR.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            VfsErr::Io(e) => HttpError {
-                code: crate::wellknown::CODE_FS_IO_ERROR.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            VfsErr::Utf8(e) => HttpError {
-                code: crate::wellknown::CODE_INVALID_UTF8.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            VfsErr::StripPrefixErr(e) => HttpError {
-                code: crate::wellknown::CODE_FS_IO_ERROR.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
+            VfsErr::Domain(msg) => HttpError::new(&CODE_FS_DOMAIN).message(msg),
+            VfsErr::FileNotFound(msg) => HttpError::new(&CODE_FS_FILE_NOT_FOUND).message(msg),
+            VfsErr::SchemaFileNotFound(msg) => HttpError::new(&CODE_FS_SCHEMA_FILE_NOT_FOUND).message(msg),
+            VfsErr::AbsolutePathNotSupported(e) => {
+                HttpError::new(&CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED).message(e.to_string())
+            }
+            VfsErr::DotPathsNotSupported(e) => HttpError::new(&CODE_FS_DOT_PATHS_NOT_SUPPORTED).message(e.to_string()),
+            VfsErr::JsonErr(e) => HttpError::new(&CODE_JSON_ERR).message(e.to_string()).source(e),
+            VfsErr::Io(e) => HttpError::new(&CODE_FS_IO_ERROR).message(e.to_string()).source(e),
+            VfsErr::Utf8(e) => HttpError::new(&CODE_INVALID_UTF8).message(e.to_string()).source(e),
+            VfsErr::StripPrefixErr(e) => HttpError::new(&CODE_FS_IO_ERROR).message(e.to_string()).source(e),
         }
+        .build()
     }
 }
 
@@ -167,114 +458,37 @@ impl From<PipelineError> for HttpError {
     fn from(value: PipelineError) -> Self {
         match value {
             PipelineError::RapidScript(e) => match e {
-                RapidScriptError::SyntaxErr { msg, pos, exp } => HttpError {
-                    code: crate::wellknown::CODE_RAPID_SCRIPT_SYNTAX_ERR.to_owned(),
-                    message: msg,
-                    context: Some(HashMap::from([
-                        ("column".to_string(), pos.to_string()),
-                        ("script".to_owned(), exp), //todo is this safe to return?
-                    ])),
-                },
-                RapidScriptError::EvalErr(msg) => HttpError {
-                    code: crate::wellknown::CODE_RAPID_SCRIPT_ERR.to_owned(),
-                    message: msg,
-                    context: None,
-                },
-            },
-            PipelineError::UnsupportedProtocol(msg) => HttpError {
-                code: crate::wellknown::CODE_UNSUPPORTED_PROTOCOL.to_owned(),
-                message: msg,
-                context: None,
+                RapidScriptError::SyntaxErr { msg, pos, exp } => HttpError::new(&CODE_RAPID_SCRIPT_SYNTAX_ERR)
+                    .message(msg)
+                    .with("column", pos)
+                    .with("script", exp), //hidden from clients by the default RedactionPolicy
+                RapidScriptError::EvalErr(msg) => HttpError::new(&CODE_RAPID_SCRIPT_ERR).message(msg),
             },
-            PipelineError::Json(e) => HttpError {
-                code: crate::wellknown::CODE_JSON_ERR.to_owned(),
-                message: format!("{}", e),
-                context: None,
-            },
-            PipelineError::BoaScript(msg) => HttpError {
-                code: crate::wellknown::CODE_SCRIPT_ERR.to_owned(),
-                message: msg,
-                context: None,
-            },
-            PipelineError::Docker(e) =>
+            PipelineError::UnsupportedProtocol(msg) => HttpError::new(&CODE_UNSUPPORTED_PROTOCOL).message(msg),
+            PipelineError::Json(e) => HttpError::new(&CODE_JSON_ERR).message(e.to_string()).source(e),
+            PipelineError::BoaScript(msg) => HttpError::new(&CODE_SCRIPT_ERR).message(msg),
             //we could break the docker error down but probably not a good idea to be too detailed about this
-                {
-                    HttpError {
-                        code: crate::wellknown::CODE_DOCKER_ERR.to_owned(),
-                        message: format!("Internal error. {}", e.to_string()),
-                        context: None,
-                    }
-                }
-            PipelineError::DockerConn(e) => HttpError {
-                code: crate::wellknown::CODE_DOCKER_ERR.to_owned(),
-                message: format!("Internal error. {}", e.to_string()),
-                context: None,
-            },
-            PipelineError::Vfs(vfs) => HttpError {
-                code: crate::wellknown::CODE_FORM_FILE_ERR.to_owned(),
-                message: format!("{}", vfs),
-                context: None,
-            },
-            PipelineError::EmptyPipeline => HttpError {
-                code: crate::wellknown::CODE_PIPELINE_EMPTY.to_owned(),
-                message: "Server error, configured pipeline has no steps to execute.".to_string(),
-                context: None,
-            },
-            PipelineError::PluginStatusErr(e) => {
-                let code = crate::wellknown::GRPC_ERRS.get(&e.code()).unwrap().to_owned();
-                // let code = grpc_code2status(e.code());
-                // let http_status = StatusCode::from_u16(code).unwrap();
-                HttpError {
-                    code,
-                    /*ErrorCode::of(
-                                                 format!("{}{}", CODE_PIPELINE_PLUGIN_STATUS_PREFIX, code,).as_str(),
-                                                 http_status,
-                                             )*/
-                    message: format!("Internal error. {}", e.to_string()),
-                    context: None,
-                }
-            }
-            PipelineError::PluginChannelErr(e) => HttpError {
-                code: ErrorCode::new(
-                    CODE_PIPELINE_PLUGIN_CONN_ERR,
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                ),
-                message: format!("Internal error. {}", e.to_string()),
-                context: None,
-            },
-            PipelineError::DuplicateSequenceId => HttpError {
-                code: crate::wellknown::CODE_PIPELINE_DUPLICATE_SEQ_ID.clone(),
-                message: "Internal error.".to_string(),
-                context: None,
-            },
-            PipelineError::PluginErr(msg, plugin) => HttpError {
-                code: crate::wellknown::CODE_PIPELINE_PLUGIN_BEHAVIOUR.clone(),
-                message: msg,
-                context: Some(HashMap::from([("plugin".into(), plugin)])),
-            },
-            PipelineError::PluginSeqErr(e) => HttpError {
-                code: ErrorCode {
-                    name: e.code.clone(),
-                    http_status: StatusCode::from_u16(e.status as u16).unwrap(),
-                },
-                message: e.message,
-                context: Some(
-                    e.context
-                        .iter()
-                        .map(|e| {
-                            (
-                                e.key.to_owned(),
-                                e.value
-                                    .first()
-                                    .map(|v| v.to_owned())
-                                    .unwrap_or_default()
-                                    .to_string(),
-                            )
-                        })
-                        .collect::<HashMap<String, String>>(),
-                ),
-            },
+            PipelineError::Docker(e) => HttpError::new(&CODE_DOCKER_ERR)
+                .message_fmt(format_args!("Internal error. {}", e))
+                .source(e),
+            PipelineError::DockerConn(e) => HttpError::new(&CODE_DOCKER_ERR)
+                .message_fmt(format_args!("Internal error. {}", e))
+                .source(e),
+            PipelineError::Vfs(vfs) => HttpError::new(&CODE_FORM_FILE_ERR).message(vfs.to_string()).source(vfs),
+            PipelineError::EmptyPipeline => HttpError::new(&CODE_PIPELINE_EMPTY)
+                .message("Server error, configured pipeline has no steps to execute."),
+            PipelineError::PluginStatusErr(e) => return e.into(),
+            PipelineError::PluginChannelErr(e) => HttpError::new(&CODE_PIPELINE_PLUGIN_CONN)
+                .message_fmt(format_args!("Internal error. {}", e))
+                .source(e),
+            PipelineError::DuplicateSequenceId => HttpError::new(&CODE_PIPELINE_DUPLICATE_SEQ_ID).message("Internal error."),
+            PipelineError::PluginErr(msg, plugin) => HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR)
+                .message(msg)
+                .with("plugin", plugin),
+            PipelineError::PluginSeqErr(e) => return e.into(),
+            PipelineError::PluginTransportErr(e) => return e.into(),
         }
+        .build()
     }
 }
 
@@ -284,16 +498,91 @@ impl From<HttpError> for OutputSequence {
             id: 0, //caller needs to set!
             value: Some(output_sequence::Value::Error(PluginError {
                 status: e.code.http_status.as_u16() as i32,
-                code: e.code.name,
+                code: e.code.name.into_owned(),
                 message: e.message,
                 context: e.context.map(|e| {
-                    e.into_iter().map(|(k, v)| Pair { key: k, value: vec![v] }).collect()
+                    e.into_iter().map(|(k, v)| Pair { key: k, value: value_to_pair_values(v) }).collect()
                 }).unwrap_or_default(),
             }))
         }
     }
 }
 
+///Reverse of `From<HttpError> for OutputSequence`, used by the host when a plugin responds with an error.
+/// Fails if the sequence is not an error.
+impl TryFrom<OutputSequence> for HttpError {
+    type Error = OutputSequenceError;
+
+    fn try_from(value: OutputSequence) -> Result<Self, Self::Error> {
+        match value.value {
+            Some(output_sequence::Value::Error(e)) => Ok(e.into()),
+            Some(output_sequence::Value::Response(_)) => Err(OutputSequenceError::NotAnError(value.id)),
+            None => Err(OutputSequenceError::Empty(value.id)),
+        }
+    }
+}
+
+impl From<PluginError> for HttpError {
+    fn from(e: PluginError) -> Self {
+        let mut context = e
+            .context
+            .into_iter()
+            .map(|p| (p.key, pair_values_to_value(p.value)))
+            .collect::<ErrorContext>();
+        //plugins can send anything, an invalid status must not take the server down
+        let code = match u16::try_from(e.status).ok().and_then(|s| StatusCode::from_u16(s).ok()) {
+            //registered codes keep their description, category and retry hint
+            Some(status) => crate::registry::lookup(e.code.as_str())
+                .filter(|c| c.http_status == status)
+                .unwrap_or_else(|| ErrorCode::new(e.code.as_str(), status)),
+            None => {
+                context.insert("plugin_code".to_string(), e.code.into());
+                context.insert("plugin_status".to_string(), e.status.into());
+                CODE_PIPELINE_PLUGIN_INVALID_STATUS
+            }
+        };
+        HttpError::new(&code).message(e.message).with_all(context).build()
+    }
+}
+
+///A single value becomes a string and several become an array of strings, unless the value is [CONTEXT_JSON_PREFIX] encoded
+fn pair_values_to_value(mut values: Vec<String>) -> Value {
+    if values.len() == 1 {
+        let value = values.remove(0);
+        match value.strip_prefix(CONTEXT_JSON_PREFIX).map(serde_json::from_str) {
+            Some(Ok(v)) => v,
+            _ => Value::String(value),
+        }
+    } else {
+        Value::Array(values.into_iter().map(Value::String).collect())
+    }
+}
+
+///Inverse of pair_values_to_value. Strings and arrays of strings are sent as plain strings so plugins in other
+/// languages can read them, anything pair_values_to_value wouldn't restore as is gets sent as prefixed JSON
+fn value_to_pair_values(value: Value) -> Vec<String> {
+    let plain = |v: &Value| matches!(v, Value::String(s) if !s.starts_with(CONTEXT_JSON_PREFIX));
+    match value {
+        Value::String(v) if !v.starts_with(CONTEXT_JSON_PREFIX) => vec![v],
+        Value::Array(values) if values.len() != 1 && values.iter().all(plain) => values
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::String(v) => Some(v),
+                _ => None,
+            })
+            .collect(),
+        v => vec![format!("{}{}", CONTEXT_JSON_PREFIX, v)],
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum OutputSequenceError {
+    #[error("Output sequence {0} has no value.")]
+    Empty(u64),
+    #[error("Output sequence {0} is not an error.")]
+    NotAnError(u64),
+}
+
 #[derive(Error, Debug)]
 pub enum PipelineError {
     #[error("Error parsing rapid script expression. {0}")]
@@ -322,6 +611,8 @@ pub enum PipelineError {
     PluginErr(String, String),
     #[error("Plugin HTTP error. {0}")]
     PluginSeqErr(PluginError),
+    #[error("Plugin transport error. {0}")]
+    PluginTransportErr(TransportError),
 }
 
 #[derive(Debug, Error)]
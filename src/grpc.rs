@@ -0,0 +1,89 @@
+use bytes::Bytes;
+use http::StatusCode;
+use prost::Message;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Code, Status};
+
+use crate::err::{ErrorCode, HttpError};
+use crate::plugin::{output_sequence, OutputSequence, PluginError};
+use crate::wellknown::{CODE_PIPELINE_PLUGIN_STATUS_PREFIX, GRPC_ERRS, HDR_ERROR_CODE};
+
+///The gRPC code closest to a HTTP status, based on https://github.com/grpc/grpc/blob/master/doc/http-grpc-status-mapping.md
+pub fn http_status_to_grpc_code(status: StatusCode) -> Code {
+    match status.as_u16() {
+        400 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        412 => Code::FailedPrecondition,
+        416 => Code::OutOfRange,
+        429 => Code::ResourceExhausted,
+        499 => Code::Cancelled,
+        501 => Code::Unimplemented,
+        503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ if status.is_success() => Code::Ok,
+        _ if status.is_client_error() => Code::FailedPrecondition,
+        _ if status.is_server_error() => Code::Internal,
+        _ => Code::Unknown,
+    }
+}
+
+///The details of the status are the error encoded as a [PluginError] so that nothing is lost,
+/// the code name is also set in the [HDR_ERROR_CODE] metadata for anything that doesn't decode the details.
+/// An error with a 2xx status becomes [Code::Unknown], clients would take [Code::Ok] as success
+impl From<HttpError> for Status {
+    fn from(value: HttpError) -> Self {
+        let code = match http_status_to_grpc_code(value.code.http_status) {
+            Code::Ok => Code::Unknown,
+            code => code,
+        };
+        let mut metadata = MetadataMap::new();
+        if let Ok(name) = MetadataValue::try_from(value.code.name()) {
+            metadata.insert(HDR_ERROR_CODE, name);
+        }
+        let message = value.message.clone();
+        let details = match OutputSequence::from(value).value {
+            Some(output_sequence::Value::Error(e)) => Bytes::from(e.encode_to_vec()),
+            _ => Bytes::new(),
+        };
+        Status::with_details_and_metadata(code, message, details, metadata)
+    }
+}
+
+///Recovers the original error from statuses created by `From<HttpError> for Status`.
+/// Any other status is mapped using [GRPC_ERRS], the status is kept as the source in both cases.
+/// Details are only decoded if the [HDR_ERROR_CODE] metadata names the same code, other servers put
+/// unrelated messages there e.g. `google.rpc.Status`
+impl From<Status> for HttpError {
+    fn from(value: Status) -> Self {
+        let code_name = value.metadata().get(HDR_ERROR_CODE).and_then(|v| v.to_str().ok());
+        let from_details = code_name
+            .and_then(|name| PluginError::decode(value.details()).ok().filter(|e| e.code == name))
+            .map(HttpError::from);
+        let err = match from_details {
+            Some(err) => err,
+            None => {
+                let code = code_name
+                    .map(|name| ErrorCode::new(name, grpc_error_code(value.code()).http_status))
+                    .unwrap_or_else(|| grpc_error_code(value.code()));
+                HttpError::new(&code).message(value.message()).build()
+            }
+        };
+        HttpError {
+            source: Some(std::sync::Arc::new(value)),
+            ..err
+        }
+    }
+}
+
+///The well known error code for a gRPC code
+pub fn grpc_error_code(code: Code) -> ErrorCode {
+    GRPC_ERRS.get(&code).cloned().unwrap_or_else(|| {
+        ErrorCode::new(
+            format!("{}unknown", CODE_PIPELINE_PLUGIN_STATUS_PREFIX).as_str(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })
+}
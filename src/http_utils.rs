@@ -1,17 +1,56 @@
-use std::convert::Infallible;
-use std::fmt::{Display, Formatter};
-use http::Method;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use bytes::Bytes;
+use http::header::RETRY_AFTER;
+use http::{HeaderName, HeaderValue, Method, Response};
+use http_body_util::Full;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use crate::err::{ErrorCode, HttpError};
-use crate::wellknown::{METHOD_CONNECT, METHOD_DELETE, METHOD_GET, METHOD_HEAD, METHOD_OPTIONS, METHOD_PATCH, METHOD_POST, METHOD_PUT, METHOD_TRACE};
+use crate::problem::ErrorFormat;
+use crate::wellknown::{APPLICATION_PROBLEM_JSON_HDR, CODE_INVALID_MEDIA_TYPE, CODE_INVALID_METHOD, CODE_MISSING_METHOD, HDR_CONTENT_TYPE, HDR_ERROR_CODE, METHOD_CONNECT, METHOD_DELETE, METHOD_GET, METHOD_HEAD, METHOD_OPTIONS, METHOD_PATCH, METHOD_POST, METHOD_PUT, METHOD_TRACE, FORM_URL_ENCODED_HDR};
 
+#[deprecated(note = "use HttpError::new(&code).message(message).build()")]
 pub fn err_msg(code: ErrorCode, message: &str) -> HttpError {
-    HttpError {
-        code,
-        message: message.to_owned(),
-        context: None,
+    HttpError::new(&code).message(message).build()
+}
+
+impl HttpError {
+    ///A response with the error's status, a content type matching the format and the redacted body.
+    /// A `Retry-After` header is added if the code has a [crate::err::RetryHint::RetryAfter] hint.
+    /// If `code_header` is set, the error code name is also sent in a header with that name e.g. [HDR_ERROR_CODE]
+    pub fn to_response(&self, format: ErrorFormat, code_header: Option<&HeaderName>) -> Response<Bytes> {
+        let mut res = Response::new(self.to_body(format));
+        *res.status_mut() = self.code.http_status;
+        let headers = res.headers_mut();
+        headers.insert(HDR_CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+        if let (Some(name), Ok(value)) = (code_header, HeaderValue::from_str(self.code.name())) {
+            headers.insert(name.clone(), value);
+        }
+        if let Some(secs) = self.code.retry().retry_after() {
+            headers.insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        res
     }
 }
-#[derive(Debug, Default, Clone, PartialEq)]
+
+///JSON response with the [HDR_ERROR_CODE] header set
+impl From<HttpError> for Response<Bytes> {
+    fn from(value: HttpError) -> Self {
+        value.to_response(ErrorFormat::Hypi, Some(&HeaderName::from_static(HDR_ERROR_CODE)))
+    }
+}
+
+///Same as the `Response<Bytes>` conversion but with a body that implements `Body` from the http-body crate, e.g. for hyper 1.x
+impl From<HttpError> for Response<Full<Bytes>> {
+    fn from(value: HttpError) -> Self {
+        Response::<Bytes>::from(value).map(Full::new)
+    }
+}
+///Serialised as the canonical upper case `METHOD_*` name, deserialised ignoring case
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum HttpMethod {
     Options,
     #[default]
@@ -23,44 +62,199 @@ pub enum HttpMethod {
     Trace,
     Connect,
     Patch,
+    ///Any other valid method e.g. WebDAV's PROPFIND or QUERY. Unlike the standard methods these are case-sensitive
+    Extension(ExtensionMethod),
 }
 
-impl HttpMethod {
-    pub fn from(value: &str) -> Option<Self> {
-        match value.to_uppercase().as_str() {
-            METHOD_OPTIONS => Some(HttpMethod::Options),
-            METHOD_GET => Some(HttpMethod::Get),
-            METHOD_POST => Some(HttpMethod::Post),
-            METHOD_PUT => Some(HttpMethod::Put),
-            METHOD_DELETE => Some(HttpMethod::Delete),
-            METHOD_HEAD => Some(HttpMethod::Head),
-            METHOD_TRACE => Some(HttpMethod::Trace),
-            METHOD_CONNECT => Some(HttpMethod::Connect),
-            METHOD_PATCH => Some(HttpMethod::Patch),
-            _ => None,
+///The longest extension method supported, long enough for every registered method
+pub const MAX_EXTENSION_METHOD_LEN: usize = 31;
+
+///The name of a non-standard method, stored inline so that [HttpMethod] can be `Copy`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExtensionMethod {
+    //bytes first so the derived Ord is alphabetical, unused bytes are 0 which sorts before any token char
+    bytes: [u8; MAX_EXTENSION_METHOD_LEN],
+    len: u8,
+}
+
+impl ExtensionMethod {
+    ///None if `http::Method` can't represent the value or it's longer than [MAX_EXTENSION_METHOD_LEN].
+    /// `http::Method` accepts fewer characters than an RFC 9110 token, `# $ % & '` are rejected,
+    /// validating against it means an [HttpMethod] always converts to an `http::Method`
+    pub fn new(value: &str) -> Option<Self> {
+        if value.is_empty() || value.len() > MAX_EXTENSION_METHOD_LEN || Method::from_bytes(value.as_bytes()).is_err() {
+            return None;
         }
+        let mut bytes = [0; MAX_EXTENSION_METHOD_LEN];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Some(Self {
+            bytes,
+            len: value.len() as u8,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        //only ever created from a token which is ASCII
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
     }
 }
-impl Display for HttpMethod {
+
+impl Debug for ExtensionMethod {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for ExtensionMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
         match self {
-            HttpMethod::Options => f.write_str(METHOD_OPTIONS),
-            HttpMethod::Get => f.write_str(METHOD_GET),
-            HttpMethod::Post => f.write_str(METHOD_POST),
-            HttpMethod::Put => f.write_str(METHOD_PUT),
-            HttpMethod::Delete => f.write_str(METHOD_DELETE),
-            HttpMethod::Head => f.write_str(METHOD_HEAD),
-            HttpMethod::Trace => f.write_str(METHOD_TRACE),
-            HttpMethod::Connect => f.write_str(METHOD_CONNECT),
-            HttpMethod::Patch => f.write_str(METHOD_PATCH),
+            HttpMethod::Options => METHOD_OPTIONS,
+            HttpMethod::Get => METHOD_GET,
+            HttpMethod::Post => METHOD_POST,
+            HttpMethod::Put => METHOD_PUT,
+            HttpMethod::Delete => METHOD_DELETE,
+            HttpMethod::Head => METHOD_HEAD,
+            HttpMethod::Trace => METHOD_TRACE,
+            HttpMethod::Connect => METHOD_CONNECT,
+            HttpMethod::Patch => METHOD_PATCH,
+            HttpMethod::Extension(method) => method.as_str(),
+        }
+    }
+
+    #[deprecated(note = "use value.parse::<HttpMethod>() which also reports why a method is invalid")]
+    pub fn from(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+
+    ///Safe methods are read-only (RFC 9110 section 9.2.1). Extensions are assumed not to be
+    pub fn is_safe(&self) -> bool {
+        matches!(self, HttpMethod::Get | HttpMethod::Head | HttpMethod::Options | HttpMethod::Trace)
+    }
+
+    ///Repeating the request has the same effect as sending it once (RFC 9110 section 9.2.2), so it can be retried
+    pub fn is_idempotent(&self) -> bool {
+        self.is_safe() || matches!(self, HttpMethod::Put | HttpMethod::Delete)
+    }
+
+    ///Responses can be cached without explicit freshness information (RFC 9110 section 9.2.3).
+    /// POST responses are only cacheable with explicit freshness info and a Content-Location so they're excluded
+    pub fn is_cacheable(&self) -> bool {
+        matches!(self, HttpMethod::Get | HttpMethod::Head)
+    }
+
+    ///False for methods where RFC 9110 gives request content no defined semantics (GET, HEAD, DELETE, CONNECT)
+    /// or forbids it (TRACE)
+    pub fn allows_request_body(&self) -> bool {
+        !matches!(
+            self,
+            HttpMethod::Get | HttpMethod::Head | HttpMethod::Delete | HttpMethod::Connect | HttpMethod::Trace
+        )
+    }
+
+    ///The value for an `Allow` or `Access-Control-Allow-Methods` header e.g. `GET, HEAD, POST`.
+    /// Duplicates are removed and methods are listed in [HttpMethod] order so the value is stable regardless of input order
+    pub fn allow_header(methods: impl IntoIterator<Item = HttpMethod>) -> HeaderValue {
+        let methods = methods.into_iter().collect::<BTreeSet<_>>();
+        let value = methods.iter().map(HttpMethod::as_str).collect::<Vec<_>>().join(", ");
+        //every method is a token so the value is always valid
+        HeaderValue::from_str(value.as_str()).unwrap_or_else(|_| HeaderValue::from_static(""))
+    }
+
+    ///The standard method with this name, ignoring case
+    fn standard(value: &str) -> Option<Self> {
+        [
+            HttpMethod::Options,
+            HttpMethod::Get,
+            HttpMethod::Post,
+            HttpMethod::Put,
+            HttpMethod::Delete,
+            HttpMethod::Head,
+            HttpMethod::Trace,
+            HttpMethod::Connect,
+            HttpMethod::Patch,
+        ]
+        .into_iter()
+        .find(|m| m.as_str().eq_ignore_ascii_case(value))
+    }
+}
+
+///A token as defined by RFC 9110 section 5.6.2
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+impl FromStr for HttpMethod {
+    type Err = HttpError;
+
+    ///Standard methods are matched ignoring case, anything else [ExtensionMethod::new] accepts is an [HttpMethod::Extension]
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.is_empty() {
+            return Err(HttpError::new(&CODE_MISSING_METHOD).message("No HTTP method provided.").build());
         }
+        HttpMethod::standard(value)
+            .or_else(|| ExtensionMethod::new(value).map(HttpMethod::Extension))
+            .ok_or_else(|| {
+                HttpError::new(&CODE_INVALID_METHOD)
+                    .message_fmt(format_args!("{} is not a valid HTTP method.", value))
+                    .with("method", value)
+                    .build()
+            })
+    }
+}
+
+impl TryFrom<&str> for HttpMethod {
+    type Error = HttpError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
-impl TryFrom<&HttpMethod> for Method {
-    type Error = Infallible;
 
-    fn try_from(value: &HttpMethod) -> Result<Self, Self::Error> {
-        Ok(match value {
+///Fails if the method is an extension longer than [MAX_EXTENSION_METHOD_LEN]
+impl TryFrom<&Method> for HttpMethod {
+    type Error = HttpError;
+
+    fn try_from(value: &Method) -> Result<Self, Self::Error> {
+        value.as_str().parse()
+    }
+}
+
+impl Serialize for HttpMethod {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpMethod {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let value = Cow::<'de, str>::deserialize(deserializer)?;
+        value.parse().map_err(|e: HttpError| D::Error::custom(e.message))
+    }
+}
+
+impl Display for HttpMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl From<&HttpMethod> for Method {
+    fn from(value: &HttpMethod) -> Self {
+        match value {
             HttpMethod::Options => Method::OPTIONS,
             HttpMethod::Get => Method::GET,
             HttpMethod::Post => Method::POST,
@@ -70,6 +264,256 @@ impl TryFrom<&HttpMethod> for Method {
             HttpMethod::Trace => Method::TRACE,
             HttpMethod::Connect => Method::CONNECT,
             HttpMethod::Patch => Method::PATCH,
+            HttpMethod::Extension(method) => Method::from_bytes(method.as_str().as_bytes())
+                .expect("ExtensionMethod::new only accepts values http::Method accepts"),
+        }
+    }
+}
+
+impl From<HttpMethod> for Method {
+    fn from(value: HttpMethod) -> Self {
+        Method::from(&value)
+    }
+}
+
+///A parsed `Content-Type` or `Accept` media type (RFC 9110 section 8.3.1) e.g. `multipart/form-data; boundary=x`.
+/// The type, subtype, suffix and parameter names are lower cased. Parameter values are kept as sent since some, like boundary, are case-sensitive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaType {
+    ty: String,
+    subtype: String,
+    suffix: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    ///The top level type e.g. `application`
+    pub fn ty(&self) -> &str {
+        self.ty.as_str()
+    }
+
+    ///The subtype without any structured syntax suffix e.g. `problem` for `application/problem+json`
+    pub fn subtype(&self) -> &str {
+        self.subtype.as_str()
+    }
+
+    ///The structured syntax suffix (RFC 6838 section 4.2.8) e.g. `json` for `application/problem+json`
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    ///The media type without parameters e.g. `application/problem+json`
+    pub fn essence(&self) -> String {
+        match &self.suffix {
+            Some(suffix) => format!("{}/{}+{}", self.ty, self.subtype, suffix),
+            None => format!("{}/{}", self.ty, self.subtype),
+        }
+    }
+
+    ///The value of a parameter, the name is matched ignoring case
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    pub fn boundary(&self) -> Option<&str> {
+        self.param("boundary")
+    }
+
+    ///`application/json` or any type with a `+json` suffix, e.g. problem+json
+    pub fn is_json(&self) -> bool {
+        self.suffix.is_none() && self.subtype == "json" || self.suffix() == Some("json")
+    }
+
+    pub fn is_problem_json(&self) -> bool {
+        self.essence() == APPLICATION_PROBLEM_JSON_HDR
+    }
+
+    pub fn is_form(&self) -> bool {
+        self.essence() == FORM_URL_ENCODED_HDR
+    }
+
+    ///Any multipart type e.g. `multipart/form-data` or `multipart/mixed`
+    pub fn is_multipart(&self) -> bool {
+        self.ty == "multipart"
+    }
+
+    ///True if `other` has the same essence or falls in this range e.g. `*/*` or `text/*`.
+    /// Every parameter on this must also be on `other` with the same value
+    pub fn matches(&self, other: &MediaType) -> bool {
+        let ty = self.ty == "*" || self.ty == other.ty;
+        let subtype = self.ty == "*"
+            || self.subtype == "*"
+            || (self.subtype == other.subtype && self.suffix == other.suffix);
+        ty && subtype && self.params.iter().all(|(k, v)| other.param(k) == Some(v.as_str()))
+    }
+
+    ///Used to pick the most specific matching range, `type/subtype` beats `type/*` which beats `*/*`
+    fn specificity(&self) -> usize {
+        (self.ty != "*") as usize + (self.subtype != "*") as usize + self.params.len()
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = HttpError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            HttpError::new(&CODE_INVALID_MEDIA_TYPE)
+                .message_fmt(format_args!("{} is not a valid media type.", value))
+                .with("media_type", value)
+                .build()
+        };
+        let mut parts = split_unquoted(value, ';').into_iter();
+        let (ty, subtype) = parts
+            .next()
+            .and_then(|essence| essence.trim().split_once('/'))
+            .filter(|(ty, subtype)| is_token(ty) && is_token(subtype))
+            .ok_or_else(invalid)?;
+        let (subtype, suffix) = match subtype.rsplit_once('+') {
+            Some((subtype, suffix)) if !subtype.is_empty() && !suffix.is_empty() => (subtype, Some(suffix)),
+            _ => (subtype, None),
+        };
+        let mut params = vec![];
+        for param in parts.map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = param.split_once('=').ok_or_else(invalid)?;
+            let name = name.trim();
+            if !is_token(name) {
+                return Err(invalid());
+            }
+            params.push((name.to_ascii_lowercase(), unquote(value.trim()).ok_or_else(invalid)?));
+        }
+        Ok(MediaType {
+            ty: ty.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            suffix: suffix.map(|s| s.to_ascii_lowercase()),
+            params,
         })
     }
 }
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.essence().as_str())?;
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                write!(f, "; {}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///A parsed `Accept` header, used to pick the best of the media types an endpoint can produce
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accept {
+    ranges: Vec<(MediaType, f32)>,
+}
+
+impl Accept {
+    ///Invalid ranges are skipped rather than failing the request. A missing or empty header accepts anything
+    pub fn parse(value: &str) -> Self {
+        let ranges = split_unquoted(value, ',')
+            .into_iter()
+            .filter_map(|range| {
+                let mut media = range.trim().parse::<MediaType>().ok()?;
+                //parameters after q are accept-ext, not part of the media range
+                let q = match media.params.iter().position(|(k, _)| k == "q") {
+                    Some(idx) => {
+                        let q = media.params[idx].1.parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+                        media.params.truncate(idx);
+                        q
+                    }
+                    None => 1.0,
+                };
+                Some((media, q))
+            })
+            .collect();
+        Accept { ranges }
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = (&MediaType, f32)> {
+        self.ranges.iter().map(|(m, q)| (m, *q))
+    }
+
+    ///The q-value of the most specific range matching the media type, 0 if none match
+    pub fn quality(&self, media: &MediaType) -> f32 {
+        if self.ranges.is_empty() {
+            return 1.0;
+        }
+        self.ranges
+            .iter()
+            .filter(|(range, _)| range.matches(media))
+            .max_by_key(|(range, _)| range.specificity())
+            .map(|(_, q)| *q)
+            .unwrap_or(0.0)
+    }
+
+    ///The acceptable media type with the highest q-value or None if the client accepts none of them.
+    /// Ties go to the type listed first in `available` so list them in the endpoint's order of preference
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        let mut best: Option<(&'a str, f32)> = None;
+        for candidate in available {
+            let q = match candidate.parse::<MediaType>() {
+                Ok(media) => self.quality(&media),
+                Err(_) => continue,
+            };
+            if q > best.map_or(0.0, |(_, best_q)| best_q) {
+                best = Some((candidate, q));
+            }
+        }
+        best.map(|(media, _)| media)
+    }
+}
+
+///Splits on `sep` except inside quoted strings
+fn split_unquoted(value: &str, sep: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (idx, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(&value[start..idx]);
+            start = idx + c.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+///A parameter value is either a token or a quoted string (RFC 9110 section 5.6.4)
+fn unquote(value: &str) -> Option<String> {
+    let Some(quoted) = value.strip_prefix('"') else {
+        return is_token(value).then(|| value.to_string());
+    };
+    let quoted = quoted.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            '"' => return None,
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
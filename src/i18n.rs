@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::err::{ErrorCode, HttpError};
+
+///The language used when none of the requested ones have a template
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+///Message templates by language and error code name.
+/// Templates can refer to context entries as `{key}` and to the original message as `{message}` e.g.
+/// `"Le plugin {plugin} a échoué"`
+#[derive(Debug, Default, Clone)]
+pub struct MessageCatalogue {
+    templates: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, language: &str, code: &ErrorCode, template: &str) {
+        self.templates
+            .entry(language.to_lowercase())
+            .or_default()
+            .insert(code.name().to_string(), template.to_string());
+    }
+
+    pub fn with(mut self, language: &str, code: &ErrorCode, template: &str) -> Self {
+        self.add(language, code, template);
+        self
+    }
+
+    ///Adds every template in a JSON object of error code name -> template e.g. a translation file
+    pub fn add_json(&mut self, language: &str, json: &str) -> Result<(), serde_json::Error> {
+        let templates: HashMap<String, String> = serde_json::from_str(json)?;
+        self.templates
+            .entry(language.to_lowercase())
+            .or_default()
+            .extend(templates);
+        Ok(())
+    }
+
+    ///The best template for the code given an Accept-Language header value, falling back to [DEFAULT_LANGUAGE]
+    pub fn resolve(&self, code: &str, accept_language: Option<&str>) -> Option<&str> {
+        accept_language
+            .map(parse_accept_language)
+            .unwrap_or_default()
+            .iter()
+            .find_map(|(lang, _)| self.template(lang, code))
+            .or_else(|| self.template(DEFAULT_LANGUAGE, code))
+    }
+
+    ///The error's message in the best language available, or its original message if there's no template for it
+    pub fn localise(&self, err: &HttpError, accept_language: Option<&str>) -> String {
+        match self.resolve(err.code.name(), accept_language) {
+            Some(template) => interpolate(template, err),
+            None => err.message.to_owned(),
+        }
+    }
+
+    ///Tries the full tag first (pt-br) then the primary language (pt)
+    fn template(&self, language: &str, code: &str) -> Option<&str> {
+        let find = |lang: &str| self.templates.get(lang).and_then(|t| t.get(code)).map(|t| t.as_str());
+        find(language).or_else(|| language.split('-').next().and_then(find))
+    }
+}
+
+impl HttpError {
+    ///A [HttpError::redacted] copy of the error with its message translated, see [MessageCatalogue::localise].
+    /// Templates only see what the [crate::redact::RedactionPolicy] lets through and the translation is kept when the response is built
+    pub fn localised(&self, catalogue: &MessageCatalogue, accept_language: Option<&str>) -> HttpError {
+        let mut err = self.redacted();
+        err.message = catalogue.localise(&err, accept_language);
+        err
+    }
+}
+
+///Language tags from an Accept-Language header, lower case and ordered by q-value (highest first).
+/// `*` and tags with q=0 are dropped
+pub fn parse_accept_language(value: &str) -> Vec<(String, f32)> {
+    let mut languages = value
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let tag = params.next()?.trim().to_lowercase();
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .next()
+                .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            if tag.is_empty() || tag == "*" || q <= 0.0 {
+                None
+            } else {
+                Some((tag, q))
+            }
+        })
+        .collect::<Vec<_>>();
+    //stable so equal q-values keep the order the client sent them in
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages
+}
+
+///Replaces `{key}` with the context value for key and `{message}` with the error's message.
+/// Unknown placeholders are left as they are
+fn interpolate(template: &str, err: &HttpError) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => {
+                let key = &after[..end];
+                match (key, err.context_value(key)) {
+                    (_, Some(Value::String(v))) => out.push_str(v),
+                    (_, Some(v)) => out.push_str(v.to_string().as_str()),
+                    ("message", None) => out.push_str(err.message.as_str()),
+                    _ => out.push_str(&rest[start..start + end + 2]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -3,4 +3,14 @@ include!(concat!(env!("OUT_DIR"), "/plugin.rs"));
 pub mod http_utils;
 pub mod wellknown;
 pub mod err;
+pub mod redact;
+pub mod problem;
+pub mod grpc;
+pub mod registry;
+pub mod catalogue;
+pub mod i18n;
+pub mod sdk;
+pub mod client;
+pub mod transport;
+pub mod meta;
 pub use hypi_rapid_plugin as plugin;
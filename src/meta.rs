@@ -0,0 +1,146 @@
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::err::HttpError;
+use crate::http_utils::HttpMethod;
+use crate::plugin::{InputSequence, Pair};
+use crate::wellknown::{META_HTTP_ENDPOINT_NAME, META_HTTP_METHOD};
+
+///Typed view of the meta data and headers RAPID sends with each request, see [RequestMetaBuilder] for the host side
+#[derive(Debug, Clone, Copy)]
+pub struct RequestMeta<'a> {
+    input: &'a InputSequence,
+}
+
+impl<'a> RequestMeta<'a> {
+    pub fn new(input: &'a InputSequence) -> Self {
+        RequestMeta { input }
+    }
+
+    ///The method of the HTTP request that triggered the pipeline.
+    /// Fails with [crate::wellknown::CODE_MISSING_METHOD] if RAPID didn't send one or [crate::wellknown::CODE_INVALID_METHOD] if it's invalid
+    #[allow(clippy::result_large_err)] //HttpError is what plugin handlers return
+    pub fn method(&self) -> Result<HttpMethod, HttpError> {
+        self.meta(META_HTTP_METHOD).unwrap_or_default().parse()
+    }
+
+    ///Same as [RequestMeta::method] but a missing method is taken to be GET, an invalid one still fails
+    #[allow(clippy::result_large_err)]
+    pub fn method_or_get(&self) -> Result<HttpMethod, HttpError> {
+        self.meta(META_HTTP_METHOD).map_or(Ok(HttpMethod::Get), str::parse)
+    }
+
+    ///The name of the endpoint that triggered the pipeline, only set if the endpoint has a name
+    pub fn endpoint(&self) -> Option<&'a str> {
+        self.meta(META_HTTP_ENDPOINT_NAME)
+    }
+
+    ///The first value of a meta data entry
+    pub fn meta(&self, key: &str) -> Option<&'a str> {
+        first(&self.input.meta, |k| k == key)
+    }
+
+    ///The first value of a header, the name is matched ignoring case
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        first(&self.input.headers, |k| k.eq_ignore_ascii_case(name))
+    }
+
+    ///Every value of a header, the name is matched ignoring case
+    pub fn header_values(&self, name: &str) -> impl Iterator<Item = &'a str> + 'a {
+        let name = name.to_owned();
+        self.input
+            .headers
+            .iter()
+            .filter(move |p| p.key.eq_ignore_ascii_case(name.as_str()))
+            .flat_map(|p| p.value.iter().map(String::as_str))
+    }
+
+    ///The headers as a [HeaderMap], names or values that aren't valid in HTTP are skipped
+    pub fn header_map(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for pair in &self.input.headers {
+            let Ok(name) = HeaderName::from_bytes(pair.key.as_bytes()) else {
+                continue;
+            };
+            for value in pair.value.iter().filter_map(|v| HeaderValue::from_str(v).ok()) {
+                headers.append(name.clone(), value);
+            }
+        }
+        headers
+    }
+}
+
+fn first(pairs: &[Pair], matches: impl Fn(&str) -> bool) -> Option<&str> {
+    pairs
+        .iter()
+        .find(|p| matches(p.key.as_str()))
+        .and_then(|p| p.value.first())
+        .map(String::as_str)
+}
+
+impl InputSequence {
+    pub fn request_meta(&self) -> RequestMeta<'_> {
+        RequestMeta::new(self)
+    }
+}
+
+///Writes the meta data and headers of an [InputSequence] in the encoding [RequestMeta] reads.
+/// Header names are lower cased and all values of a header are sent in one [Pair]
+#[derive(Debug, Default, Clone)]
+pub struct RequestMetaBuilder {
+    meta: Vec<Pair>,
+    headers: Vec<Pair>,
+}
+
+impl RequestMetaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn method(self, method: HttpMethod) -> Self {
+        self.meta(META_HTTP_METHOD, method.as_str())
+    }
+
+    pub fn endpoint(self, name: &str) -> Self {
+        self.meta(META_HTTP_ENDPOINT_NAME, name)
+    }
+
+    ///Replaces any existing value for the key
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.meta.retain(|p| p.key != key);
+        self.meta.push(Pair {
+            key: key.to_string(),
+            value: vec![value.to_string()],
+        });
+        self
+    }
+
+    ///Adds a value to the header, keeping any existing values
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        match self.headers.iter_mut().find(|p| p.key == name) {
+            Some(pair) => pair.value.push(value.to_string()),
+            None => self.headers.push(Pair {
+                key: name,
+                value: vec![value.to_string()],
+            }),
+        }
+        self
+    }
+
+    ///Adds every header in the map, values that aren't UTF-8 are converted lossily
+    pub fn headers(self, headers: &HeaderMap) -> Self {
+        headers.iter().fold(self, |builder, (name, value)| {
+            builder.header(name.as_str(), String::from_utf8_lossy(value.as_bytes()).as_ref())
+        })
+    }
+
+    ///An input with the meta data and headers set, fill in the rest with struct update syntax e.g.
+    /// `InputSequence { body, ..builder.build() }`. The id is set by [crate::client::PluginClient]
+    pub fn build(self) -> InputSequence {
+        InputSequence {
+            meta: self.meta,
+            headers: self.headers,
+            ..Default::default()
+        }
+    }
+}
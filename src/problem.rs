@@ -0,0 +1,85 @@
+use bytes::Bytes;
+use log::warn;
+use serde_json::{json, Map, Value};
+
+use crate::err::HttpError;
+use crate::http_utils::Accept;
+use crate::wellknown::{APPLICATION_JSON_HDR, APPLICATION_PROBLEM_JSON_HDR};
+
+///Members defined by RFC 9457 and the extensions every problem has, context entries with these keys are not copied into a problem
+const PROBLEM_MEMBERS: [&str; 8] = ["type", "title", "status", "detail", "instance", "category", "retry", "retry_after"];
+
+///How a [HttpError] is rendered in a response body
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    ///RAPID's own `{code, message, context}` JSON
+    #[default]
+    Hypi,
+    ///RFC 9457 `application/problem+json`
+    Problem,
+}
+
+impl ErrorFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ErrorFormat::Hypi => APPLICATION_JSON_HDR,
+            ErrorFormat::Problem => APPLICATION_PROBLEM_JSON_HDR,
+        }
+    }
+
+    ///Problem if the client prefers `application/problem+json` over `application/json`, otherwise Hypi
+    pub fn negotiate(accept: &Accept) -> Self {
+        match accept.negotiate(&[APPLICATION_JSON_HDR, APPLICATION_PROBLEM_JSON_HDR]) {
+            Some(APPLICATION_PROBLEM_JSON_HDR) => ErrorFormat::Problem,
+            _ => ErrorFormat::Hypi,
+        }
+    }
+}
+
+impl HttpError {
+    ///The error as an RFC 9457 problem object.
+    /// The code name becomes `type`, the HTTP status `status` and its reason phrase `title`,
+    /// the message becomes `detail` and context entries become extension members along with the code's category and retry hint.
+    /// This is not redacted, use [HttpError::to_body] for response bodies
+    pub fn to_problem(&self) -> Value {
+        let mut problem = Map::new();
+        problem.insert("type".to_string(), json!(self.code.name));
+        if let Some(title) = self.code.http_status.canonical_reason() {
+            problem.insert("title".to_string(), json!(title));
+        }
+        problem.insert("status".to_string(), json!(self.code.http_status.as_u16()));
+        problem.insert("detail".to_string(), json!(self.message));
+        problem.insert("category".to_string(), json!(self.code.category()));
+        let retry = self.code.retry();
+        problem.insert("retry".to_string(), json!(retry));
+        if let Some(secs) = retry.retry_after() {
+            problem.insert("retry_after".to_string(), json!(secs));
+        }
+        if let Some(context) = &self.context {
+            for (k, v) in context {
+                if !PROBLEM_MEMBERS.contains(&k.as_str()) {
+                    problem.insert(k.to_owned(), v.to_owned());
+                }
+            }
+        }
+        Value::Object(problem)
+    }
+
+    ///The redacted response body for the error in the given format, see [ErrorFormat::content_type] for its content type
+    pub fn to_body(&self, format: ErrorFormat) -> Bytes {
+        match format {
+            ErrorFormat::Hypi => Bytes::from(self),
+            ErrorFormat::Problem => serde_json::to_vec(&self.redacted().to_problem())
+                .unwrap_or_else(|e| {
+                    warn!("Unable to serialise problem for code {}. {}", self.code, e);
+                    json!({
+                        "type": self.code.name,
+                        "status": self.code.http_status.as_u16(),
+                    })
+                    .to_string()
+                    .into_bytes()
+                })
+                .into(),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::err::{ErrorCode, HttpError};
+use crate::wellknown::{CODE_DOCKER_ERR, CODE_PIPELINE_PLUGIN_CONN, CODE_PIPELINE_PLUGIN_TRANSPORT, GRPC_ERRS};
+
+///The message clients get in place of one that has been redacted
+pub const REDACTED_MESSAGE: &str = "Internal error.";
+
+lazy_static! {
+    static ref POLICY: RwLock<RedactionPolicy> = RwLock::new(RedactionPolicy::default());
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionMode {
+    ///Messages and context keys covered by the policy are removed from responses
+    #[default]
+    Production,
+    ///Nothing is redacted, only use this for local development
+    Debug,
+}
+
+///Decides what part of a [HttpError] is safe to send to a client.
+/// The full error, including its source, is still available for logs via [HttpError::chain].
+#[derive(Debug, Clone)]
+pub struct RedactionPolicy {
+    pub mode: RedactionMode,
+    context_keys: HashSet<String>,
+    ///Error code name -> message sent to the client instead of the real one
+    messages: HashMap<String, String>,
+}
+
+impl RedactionPolicy {
+    ///A policy that doesn't redact anything until keys and codes are added to it
+    pub fn new(mode: RedactionMode) -> Self {
+        Self {
+            mode,
+            context_keys: HashSet::new(),
+            messages: HashMap::new(),
+        }
+    }
+
+    ///Never send the context entry with this key to clients
+    pub fn hide_context_key(mut self, key: &str) -> Self {
+        self.context_keys.insert(key.to_string());
+        self
+    }
+
+    ///Send `public_message` instead of the real message for errors with this code
+    pub fn hide_message(mut self, code: &ErrorCode, public_message: &str) -> Self {
+        self.messages.insert(code.name().to_string(), public_message.to_string());
+        self
+    }
+
+    pub fn is_context_key_hidden(&self, key: &str) -> bool {
+        self.mode == RedactionMode::Production && self.context_keys.contains(key)
+    }
+
+    ///Returns a copy of the error with everything this policy hides removed
+    pub fn redact(&self, err: &HttpError) -> HttpError {
+        let mut redacted = HttpError::from(err);
+        if self.mode == RedactionMode::Debug {
+            return redacted;
+        }
+        if !err.message_redacted {
+            if let Some(msg) = self.messages.get(err.code.name()) {
+                redacted.message = msg.to_owned();
+            }
+            redacted.message_redacted = true;
+        }
+        if let Some(context) = redacted.context.as_mut() {
+            context.retain(|k, _| !self.context_keys.contains(k));
+        }
+        redacted.context = redacted.context.filter(|c| !c.is_empty());
+        redacted
+    }
+}
+
+///Production mode, hides RAPID scripts and internal details of Docker and plugin failures
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        let policy = RedactionPolicy::new(RedactionMode::Production)
+            .hide_context_key("script")
+            .hide_message(&CODE_DOCKER_ERR, REDACTED_MESSAGE);
+        GRPC_ERRS
+            .values()
+            .fold(policy, |p, code| p.hide_message(code, REDACTED_MESSAGE))
+            .hide_message(&CODE_PIPELINE_PLUGIN_CONN, REDACTED_MESSAGE)
+            .hide_message(&CODE_PIPELINE_PLUGIN_TRANSPORT, REDACTED_MESSAGE)
+    }
+}
+
+///Replaces the policy used when a [HttpError] is turned into a response body. Call this once at startup
+pub fn set_redaction_policy(policy: RedactionPolicy) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+pub fn redaction_policy() -> RedactionPolicy {
+    POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+impl HttpError {
+    ///A copy of this error that is safe to send to clients according to the current [RedactionPolicy]
+    pub fn redacted(&self) -> HttpError {
+        POLICY.read().unwrap_or_else(|e| e.into_inner()).redact(self)
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use log::warn;
+use thiserror::Error;
+
+use crate::err::ErrorCode;
+
+lazy_static! {
+    static ref REGISTRY: RwLock<ErrorCodeRegistry> = RwLock::new(ErrorCodeRegistry::with_well_known());
+}
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("Error code {name} is already registered with HTTP status {existing}.")]
+    Duplicate { name: String, existing: u16 },
+}
+
+///Error codes by name, a name can only be registered once
+#[derive(Debug, Default, Clone)]
+pub struct ErrorCodeRegistry {
+    codes: BTreeMap<String, ErrorCode>,
+}
+
+impl ErrorCodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///A registry with every code from [crate::wellknown::error_codes].
+    /// Duplicates are logged and the first one kept, the tests make sure there are none
+    pub fn with_well_known() -> Self {
+        let mut registry = Self::new();
+        for code in crate::wellknown::error_codes() {
+            if let Err(e) = registry.register(code) {
+                warn!("Duplicate well known error code. {}", e);
+            }
+        }
+        registry
+    }
+
+    pub fn register(&mut self, code: ErrorCode) -> Result<(), RegistryError> {
+        if let Some(existing) = self.codes.get(code.name()) {
+            return Err(RegistryError::Duplicate {
+                name: code.name.into_owned(),
+                existing: existing.http_status.as_u16(),
+            });
+        }
+        self.codes.insert(code.name().to_string(), code);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ErrorCode> {
+        self.codes.get(name)
+    }
+
+    ///All codes, ordered by name
+    pub fn codes(&self) -> impl Iterator<Item = &ErrorCode> {
+        self.codes.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+}
+
+///Adds a code to the global registry, downstream crates should register their codes once at startup
+pub fn register(code: ErrorCode) -> Result<(), RegistryError> {
+    REGISTRY.write().unwrap_or_else(|e| e.into_inner()).register(code)
+}
+
+///Registers all the codes, stopping at the first duplicate
+pub fn register_all<I: IntoIterator<Item = ErrorCode>>(codes: I) -> Result<(), RegistryError> {
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    codes.into_iter().try_for_each(|code| registry.register(code))
+}
+
+pub fn lookup(name: &str) -> Option<ErrorCode> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).get(name).cloned()
+}
+
+///A copy of every registered code, ordered by name
+pub fn registered_codes() -> Vec<ErrorCode> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .codes()
+        .cloned()
+        .collect()
+}
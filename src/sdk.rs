@@ -0,0 +1,138 @@
+use std::io;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::Arc;
+
+use futures_util::FutureExt;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::StreamExt;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::err::HttpError;
+use crate::plugin::rapid_plugin_server::{RapidPlugin as RapidPluginService, RapidPluginServer};
+use crate::plugin::{output_sequence, InputSequence, OutputSequence, PluginResponse};
+use crate::transport::{Pipe, TransportError};
+use crate::wellknown::CODE_PIPELINE_PLUGIN_BEHAVIOUR;
+
+///How many responses can be queued for RAPID before handlers wait for the stream to catch up
+const OUTPUT_BUFFER: usize = 64;
+
+///Implemented by Rust plugins to handle a single request.
+/// RAPID multiplexes requests over one stream, [PluginService] handles each one concurrently and
+/// sends the result back with the id of the request it answers so implementations never deal with sequence ids
+#[tonic::async_trait]
+pub trait RapidPlugin: Send + Sync + 'static {
+    async fn handle(&self, request: InputSequence) -> Result<PluginResponse, HttpError>;
+}
+
+///Adapts a [RapidPlugin] to the generated tonic service
+pub struct PluginService<P> {
+    plugin: Arc<P>,
+}
+
+impl<P: RapidPlugin> PluginService<P> {
+    pub fn new(plugin: P) -> Self {
+        Self::from_arc(Arc::new(plugin))
+    }
+
+    pub fn from_arc(plugin: Arc<P>) -> Self {
+        PluginService { plugin }
+    }
+
+    ///The tonic server for the plugin, for adding to a [Server] alongside other services
+    pub fn into_server(self) -> RapidPluginServer<Self> {
+        RapidPluginServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl<P: RapidPlugin> RapidPluginService for PluginService<P> {
+    type ExecuteStream = ReceiverStream<Result<OutputSequence, Status>>;
+
+    async fn execute(
+        &self,
+        request: Request<Streaming<InputSequence>>,
+    ) -> Result<Response<Self::ExecuteStream>, Status> {
+        let mut inputs = request.into_inner();
+        let (tx, rx) = mpsc::channel(OUTPUT_BUFFER);
+        let plugin = self.plugin.clone();
+        tokio::spawn(async move {
+            while let Some(input) = inputs.next().await {
+                let input = match input {
+                    Ok(input) => input,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        break;
+                    }
+                };
+                let plugin = plugin.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let id = input.id;
+                    let result = match AssertUnwindSafe(plugin.handle(input)).catch_unwind().await {
+                        Ok(result) => result,
+                        Err(_) => Err(HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR)
+                            .message("Internal error. Plugin panicked while handling the request.")
+                            .build()),
+                    };
+                    //fails only if RAPID closed the stream in which case there's nobody to respond to
+                    let _ = tx.send(Ok(to_output(id, result))).await;
+                });
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+///The sequence sent back to RAPID for the result of handling request `id`
+fn to_output(id: u64, result: Result<PluginResponse, HttpError>) -> OutputSequence {
+    match result {
+        Ok(response) => OutputSequence {
+            id,
+            value: Some(output_sequence::Value::Response(response)),
+        },
+        Err(e) => OutputSequence { id, ..e.into() },
+    }
+}
+
+///Serves the plugin over gRPC on `addr`, only returns if the server fails
+pub async fn serve<P: RapidPlugin>(plugin: P, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(PluginService::new(plugin).into_server())
+        .serve(addr)
+        .await
+}
+
+///Serves the plugin on a Unix domain socket at `path`, only returns if the server fails
+#[cfg(unix)]
+pub async fn serve_unix<P: RapidPlugin>(plugin: P, path: impl AsRef<Path>) -> Result<(), TransportError> {
+    let listener = tokio::net::UnixListener::bind(path)?;
+    Server::builder()
+        .add_service(PluginService::new(plugin).into_server())
+        .serve_with_incoming(UnixListenerStream::new(listener))
+        .await?;
+    Ok(())
+}
+
+///Serves the plugin to the process that ran it, over stdin and stdout, returning once the host closes the connection.
+/// Nothing else may be written to stdout, log to stderr instead
+pub async fn serve_stdio<P: RapidPlugin>(plugin: P) -> Result<(), TransportError> {
+    //dropped along with the connection, which stops the server
+    let (connected, closed) = oneshot::channel::<()>();
+    let pipe = Pipe::new(tokio::io::stdin(), tokio::io::stdout(), connected);
+    //there is only ever one connection but the server would stop accepting it if the stream ended
+    let incoming = tokio_stream::once(Ok::<_, io::Error>(pipe)).chain(tokio_stream::pending());
+    Server::builder()
+        .add_service(PluginService::new(plugin).into_server())
+        .serve_with_incoming_shutdown(incoming, async {
+            let _ = closed.await;
+        })
+        .await?;
+    Ok(())
+}
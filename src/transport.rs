@@ -0,0 +1,254 @@
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::codegen::http::Uri;
+use tonic::codegen::Service;
+use tonic::transport::server::Connected;
+use tonic::transport::{Channel, Endpoint, Server};
+
+use crate::err::{HttpError, PipelineError};
+use crate::plugin::rapid_plugin_client::RapidPluginClient;
+use crate::plugin::rapid_plugin_server::{RapidPlugin as RapidPluginService, RapidPluginServer};
+use crate::wellknown::CODE_PIPELINE_PLUGIN_TRANSPORT;
+
+///Bytes buffered in each direction of an in-memory connection
+const DUPLEX_BUFFER: usize = 64 * 1024;
+///Used for transports that aren't addressed by a URL, the authority is only used for the :authority header
+const LOCAL_ENDPOINT: &str = "http://local.plugin";
+
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("Unsupported plugin URL {0}, expected an http, unix or stdio URL.")]
+    UnsupportedScheme(String),
+    #[error("Invalid plugin URL {0}.")]
+    InvalidUrl(String),
+    #[error("Plugin I/O error. {0}")]
+    Io(io::Error),
+    #[error("Plugin connection error. {0}")]
+    Connect(tonic::transport::Error),
+}
+
+impl From<io::Error> for TransportError {
+    fn from(value: io::Error) -> Self {
+        TransportError::Io(value)
+    }
+}
+
+impl From<tonic::transport::Error> for TransportError {
+    fn from(value: tonic::transport::Error) -> Self {
+        TransportError::Connect(value)
+    }
+}
+
+impl From<TransportError> for PipelineError {
+    fn from(value: TransportError) -> Self {
+        PipelineError::PluginTransportErr(value)
+    }
+}
+
+impl From<TransportError> for HttpError {
+    fn from(value: TransportError) -> Self {
+        HttpError::new(&CODE_PIPELINE_PLUGIN_TRANSPORT)
+            .message_fmt(format_args!("Internal error. {}", value))
+            .source(value)
+            .build()
+    }
+}
+
+///Where a plugin is reached, parsed from a URL:
+/// - `http://host:port` over TCP, TLS isn't supported so `https` URLs are rejected
+/// - `unix:///path/to/plugin.sock` over a Unix domain socket
+/// - `stdio:///path/to/plugin?arg=--flag&arg=value` runs the program and talks to it over its stdin/stdout, args are percent-decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginAddr {
+    Tcp(String),
+    Unix(PathBuf),
+    Stdio { program: PathBuf, args: Vec<String> },
+}
+
+impl FromStr for PluginAddr {
+    type Err = TransportError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || TransportError::InvalidUrl(value.to_string());
+        let (scheme, rest) = value.split_once(':').ok_or_else(invalid)?;
+        //the authority is always empty so unix:///a.sock and unix:/a.sock are the same
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" => Ok(PluginAddr::Tcp(value.to_string())),
+            "unix" if !rest.is_empty() => Ok(PluginAddr::Unix(PathBuf::from(percent_decode(rest).ok_or_else(invalid)?))),
+            "stdio" => {
+                let (program, query) = rest.split_once('?').unwrap_or((rest, ""));
+                let program = percent_decode(program).filter(|p| !p.is_empty()).ok_or_else(invalid)?;
+                let args = query
+                    .split('&')
+                    .filter(|param| !param.is_empty())
+                    .map(|param| param.strip_prefix("arg=").and_then(percent_decode))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(invalid)?;
+                Ok(PluginAddr::Stdio {
+                    program: PathBuf::from(program),
+                    args,
+                })
+            }
+            "unix" => Err(invalid()),
+            _ => Err(TransportError::UnsupportedScheme(value.to_string())),
+        }
+    }
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            decoded.push(b);
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+///Connects to the plugin at `addr`. If the connection drops the client reconnects, for stdio that means running the program again
+pub async fn connect(addr: &PluginAddr) -> Result<RapidPluginClient<Channel>, TransportError> {
+    let channel = match addr {
+        PluginAddr::Tcp(url) => Endpoint::from_shared(url.to_owned())?.connect().await?,
+        PluginAddr::Unix(path) => connect_unix(path).await?,
+        PluginAddr::Stdio { program, args } => {
+            let (program, args) = (program.to_owned(), args.to_owned());
+            Endpoint::from_static(LOCAL_ENDPOINT)
+                .connect_with_connector(Connector(move || std::future::ready(spawn(&program, &args))))
+                .await?
+        }
+    };
+    Ok(RapidPluginClient::new(channel))
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &Path) -> Result<Channel, TransportError> {
+    let path = path.to_owned();
+    let connector = Connector(move || tokio::net::UnixStream::connect(path.clone()));
+    Ok(Endpoint::from_static(LOCAL_ENDPOINT).connect_with_connector(connector).await?)
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(path: &Path) -> Result<Channel, TransportError> {
+    Err(TransportError::UnsupportedScheme(format!("unix://{}", path.display())))
+}
+
+///The plugin is killed when the connection is dropped
+fn spawn(program: &Path, args: &[String]) -> io::Result<Pipe<ChildStdout, ChildStdin, Child>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let missing = || io::Error::new(io::ErrorKind::BrokenPipe, "Plugin stdio is not piped.");
+    let stdin = child.stdin.take().ok_or_else(missing)?;
+    let stdout = child.stdout.take().ok_or_else(missing)?;
+    Ok(Pipe::new(stdout, stdin, child))
+}
+
+///Serves the plugin in-process and returns a client connected to it over an in-memory duplex stream, no sockets are opened.
+/// Intended for tests e.g. `PluginClient::new("test", in_memory(PluginService::new(plugin)))`.
+/// The server stops once the client and all its clones are dropped
+pub fn in_memory<T: RapidPluginService>(service: T) -> RapidPluginClient<Channel> {
+    let (servers, incoming) = mpsc::channel::<DuplexStream>(1);
+    tokio::spawn(
+        Server::builder()
+            .add_service(RapidPluginServer::new(service))
+            .serve_with_incoming(ReceiverStream::new(incoming).map(Ok::<_, io::Error>)),
+    );
+    //a new duplex pair for every connection the channel makes, the server gets the other end
+    let connector = Connector(move || {
+        let servers = servers.clone();
+        async move {
+            let (client, server) = tokio::io::duplex(DUPLEX_BUFFER);
+            servers
+                .send(server)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "In-memory plugin server has stopped."))?;
+            Ok(client)
+        }
+    });
+    RapidPluginClient::new(Endpoint::from_static(LOCAL_ENDPOINT).connect_with_connector_lazy(connector))
+}
+
+///Adapts a function opening a connection to the connector tonic's `Endpoint::connect_with_connector` expects
+#[derive(Clone)]
+struct Connector<F>(F);
+
+impl<F, Fut, IO> Service<Uri> for Connector<F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = io::Result<IO>> + Send + 'static,
+{
+    type Response = IO;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<IO>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        Box::pin((self.0)())
+    }
+}
+
+///Joins a reader and a writer into one connection, `keep_alive` is dropped with the connection e.g. the plugin process
+pub(crate) struct Pipe<R, W, K> {
+    reader: R,
+    writer: W,
+    _keep_alive: K,
+}
+
+impl<R, W, K> Pipe<R, W, K> {
+    pub(crate) fn new(reader: R, writer: W, keep_alive: K) -> Self {
+        Pipe {
+            reader,
+            writer,
+            _keep_alive: keep_alive,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin, W: Unpin, K: Unpin> AsyncRead for Pipe<R, W, K> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().reader).poll_read(cx, buf)
+    }
+}
+
+impl<R: Unpin, W: AsyncWrite + Unpin, K: Unpin> AsyncWrite for Pipe<R, W, K> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_shutdown(cx)
+    }
+}
+
+impl<R, W, K> Connected for Pipe<R, W, K> {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
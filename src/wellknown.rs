@@ -4,10 +4,81 @@ use http::StatusCode;
 use lazy_static::lazy_static;
 use tonic::Code;
 
-use crate::err::ErrorCode;
+use crate::err::{ErrorCategory, ErrorCode, RetryHint};
+
+///The name of every well known error code. Unlike the `CODE_` consts these can be used as patterns e.g.
+/// `match err.code.name() { names::MISSING_HOST => .., _ => .. }`
+pub mod names {
+    pub const UNKNOWN_DOMAIN: &str = "hypi_domain_not_found";
+    pub const FAILED_NO_CONTENT: &str = "hypi_failed_no_content";
+    pub const FAILED_TO_BUILD_RESPONSE: &str = "hypi_failed_to_build_response";
+    pub const ENDPOINT_INVALID_REGEX: &str = "hypi_endpoint_invalid_regex";
+    pub const MISSING_METHOD: &str = "hypi_missing_method";
+    pub const INVALID_METHOD: &str = "hypi_invalid_method";
+    pub const INVALID_MEDIA_TYPE: &str = "hypi_invalid_media_type";
+    pub const MISSING_HOST: &str = "hypi_missing_host";
+    pub const FS_SCHEMA_FILE_NOT_FOUND: &str = "hypi_schema_file_not_found";
+    pub const FS_ABSOLUTE_PATH_NOT_SUPPORTED: &str = "hypi_absolute_path_not_supported";
+    pub const FS_DOT_PATHS_NOT_SUPPORTED: &str = "hypi_dot_path_not_supported";
+    pub const FS_FILE_NOT_FOUND: &str = "hypi_file_not_found";
+    pub const FS_DOMAIN: &str = "hypi_fs_domain_not_found";
+    pub const RAPID_SCRIPT_ERR: &str = "hypi_rapid_script_err";
+    pub const RAPID_SCRIPT_SYNTAX_ERR: &str = "hypi_rapid_script_syntax_err";
+    pub const UNSUPPORTED_PROTOCOL: &str = "hypi_pipeline_unsupported_protocol";
+    pub const PIPELINE_NOT_FOUND: &str = "hypi_pipeline_not_found";
+    pub const PIPELINE_EMPTY: &str = "hypi_pipeline_empty";
+    pub const PIPELINE_EL_UNSUPPORTED: &str = "hypi_pipeline_unsupported_element";
+    pub const SCHEMA_INVALID: &str = "hypi_schema_invalid";
+    pub const SEMANTICS_INVALID_SCHEMA_ROOT: &str = "hypi_semantics_invalid_schema_root";
+    pub const SEMANTICS_DUPLICATE_TABLE: &str = "hypi_semantics_duplicate_table";
+    pub const SEMANTICS_TABLE_NOT_FOUND: &str = "hypi_semantics_table_not_found";
+    pub const FS_IO_ERROR: &str = "hypi_io_error";
+    pub const INVALID_UTF8: &str = "hypi_invalid_utf8";
+    pub const HTTP_INVALID_HEADER: &str = "hypi_http_invalid_header";
+    pub const HTTP_INVALID_URI: &str = "hypi_http_invalid_uri";
+    pub const HTTP_MISSING_HOST: &str = "hypi_http_missing_host";
+    pub const HTTP_HYPER: &str = "hypi_http_hyper";
+    pub const HTTP_IO: &str = "hypi_http_io_err";
+    pub const JSON_ERR: &str = "hypi_json_err";
+    pub const SQL_UNSUPPORTED: &str = "hypi_sql_unsupported_db_type";
+    pub const SQL_NO_CONNECTION: &str = "hypi_sql_no_connection";
+    pub const SQL_UNABLE_TO_CONNECT: &str = "hypi_sql_connection_failed";
+    pub const SQL_MEKADB_UNABLE_TO_CONNECT: &str = "hypi_sql_mekadb_connection_failed";
+    pub const SQL_MEKADB_REQ_ERR: &str = "hypi_sql_mekadb_req_err";
+    pub const SQL_MEKADB_RES_ERR: &str = "hypi_sql_mekadb_res_err";
+    pub const SCRIPT_ERR: &str = "hypi_script_err";
+    pub const PIPELINE_NO_STEPS: &str = "hypi_pipeline_no_steps";
+    pub const PIPELINE_DUPLICATE_SEQ_ID: &str = "hypi_pipeline_duplicate_seq_id";
+    pub const PIPELINE_PLUGIN_BEHAVIOUR: &str = "hypi_pipeline_bad_behaviour";
+    pub const PIPELINE_PLUGIN_INVALID_STATUS: &str = "hypi_pipeline_plugin_invalid_status";
+    pub const PIPELINE_PLUGIN_CONN: &str = "hypi_pipeline_plugin_conn_err";
+    pub const PIPELINE_PLUGIN_TRANSPORT: &str = "hypi_pipeline_plugin_transport_err";
+    pub const FORM_FILE_ERR: &str = "hypi_form_file_err";
+    pub const FORM_PAYLOAD_ERR: &str = "hypi_form_payload_err";
+    pub const FORM_IO_ERR: &str = "hypi_form_io_err";
+    pub const DOCKER_ERR: &str = "hypi_docker_err";
+    //the codes in GRPC_ERRS
+    pub const PIPELINE_PLUGIN_STATUS_OK: &str = "hypi_pipeline_plugin_status_ok";
+    pub const PIPELINE_PLUGIN_STATUS_CANCELLED: &str = "hypi_pipeline_plugin_status_cancelled";
+    pub const PIPELINE_PLUGIN_STATUS_UNKNOWN: &str = "hypi_pipeline_plugin_status_unknown";
+    pub const PIPELINE_PLUGIN_STATUS_INVALIDARGUMENT: &str = "hypi_pipeline_plugin_status_invalidargument";
+    pub const PIPELINE_PLUGIN_STATUS_DEADLINEEXCEEDED: &str = "hypi_pipeline_plugin_status_deadlineexceeded";
+    pub const PIPELINE_PLUGIN_STATUS_NOTFOUND: &str = "hypi_pipeline_plugin_status_notfound";
+    pub const PIPELINE_PLUGIN_STATUS_ALREADYEXISTS: &str = "hypi_pipeline_plugin_status_alreadyexists";
+    pub const PIPELINE_PLUGIN_STATUS_PERMISSIONDENIED: &str = "hypi_pipeline_plugin_status_permissiondenied";
+    pub const PIPELINE_PLUGIN_STATUS_UNAUTHENTICATED: &str = "hypi_pipeline_plugin_status_unauthenticated";
+    pub const PIPELINE_PLUGIN_STATUS_RESOURCEEXHAUSTED: &str = "hypi_pipeline_plugin_status_resourceexhausted";
+    pub const PIPELINE_PLUGIN_STATUS_FAILEDPRECONDITION: &str = "hypi_pipeline_plugin_status_failedprecondition";
+    pub const PIPELINE_PLUGIN_STATUS_ABORTED: &str = "hypi_pipeline_plugin_status_aborted";
+    pub const PIPELINE_PLUGIN_STATUS_OUTOFRANGE: &str = "hypi_pipeline_plugin_status_outofrange";
+    pub const PIPELINE_PLUGIN_STATUS_UNIMPLEMENTED: &str = "hypi_pipeline_plugin_status_unimplemented";
+    pub const PIPELINE_PLUGIN_STATUS_INTERNAL: &str = "hypi_pipeline_plugin_status_internal";
+    pub const PIPELINE_PLUGIN_STATUS_UNAVAILABLE: &str = "hypi_pipeline_plugin_status_unavailable";
+    pub const PIPELINE_PLUGIN_STATUS_DATALOSS: &str = "hypi_pipeline_plugin_status_dataloss";
+}
 
 pub const CODE_PIPELINE_PLUGIN_STATUS_PREFIX: &str = "hypi_pipeline_plugin_status_";
-pub const CODE_PIPELINE_PLUGIN_CONN_ERR: &str = "hypi_pipeline_plugin_conn_err";
+pub const CODE_PIPELINE_PLUGIN_CONN_ERR: &str = names::PIPELINE_PLUGIN_CONN;
 pub const METHOD_OPTIONS: &str = "OPTIONS";
 pub const METHOD_GET: &str = "GET";
 pub const METHOD_POST: &str = "POST";
@@ -19,6 +90,9 @@ pub const METHOD_CONNECT: &str = "CONNECT";
 pub const METHOD_PATCH: &str = "PATCH";
 pub const FORM_URL_ENCODED_HDR: &str = "application/x-www-form-urlencoded";
 pub const APPLICATION_JSON_HDR: &str = "application/json";
+///RFC 9457 problem details
+pub const APPLICATION_PROBLEM_JSON_HDR: &str = "application/problem+json";
+pub const MULTIPART_FORM_DATA_HDR: &str = "multipart/form-data";
 ///The HTTP method doesn't fit into other standard fields so it is captured by RAPID and put in as a meta field called method
 pub const META_HTTP_METHOD: &str = "method";
 ///The name of the HTTP endpoint that triggered the execution.
@@ -31,222 +105,512 @@ pub const ASSETS_DIR: &str = "/home/rapid/files";
 ///The path where RAPID server uploads/saves temporary files - it is up to plugins to move the files to a permanent location. RAPID automatically deletes data in this directory periodically
 pub const ASSETS_TMP_DIR: &str = "/home/rapid/files/.tmp";
 pub const HDR_CONTENT_TYPE: &str = "content-type";
+pub const HDR_ACCEPT: &str = "accept";
 pub const HDR_HOST: &str = "host";
 pub const HDR_STATUS: &str = "status";
-lazy_static! {
-    pub static ref CODE_UNKNOWN_DOMAIN: ErrorCode =
-        ErrorCode::new("hypi_domain_not_found", StatusCode::NOT_FOUND);
-    pub static ref CODE_FAILED_NO_CONTENT: ErrorCode =
-        ErrorCode::new("hypi_failed_no_content", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FAILED_TO_BUILD_RESPONSE: ErrorCode =
-        ErrorCode::new("hypi_failed_to_build_response", StatusCode::BAD_REQUEST);
-    pub static ref CODE_ENDPOINT_INVALID_REGEX: ErrorCode =
-        ErrorCode::new("hypi_endpoint_invalid_regex", StatusCode::BAD_REQUEST);
-    pub static ref CODE_MISSING_METHOD: ErrorCode =
-        ErrorCode::new("hypi_missing_method", StatusCode::BAD_REQUEST);
-    pub static ref CODE_MISSING_HOST: ErrorCode =
-        ErrorCode::new("hypi_missing_host", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_SCHEMA_FILE_NOT_FOUND: ErrorCode =
-        ErrorCode::new("hypi_schema_file_not_found", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED: ErrorCode =
-        ErrorCode::new("hypi_absolute_path_not_supported", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_DOT_PATHS_NOT_SUPPORTED: ErrorCode =
-        ErrorCode::new("hypi_dot_path_not_supported", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_FILE_NOT_FOUND: ErrorCode =
-        ErrorCode::new("hypi_file_not_found", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_DOMAIN: ErrorCode =
-        ErrorCode::new("hypi_domain_not_found", StatusCode::BAD_REQUEST);
-    pub static ref CODE_RAPID_SCRIPT_ERR: ErrorCode =
-        ErrorCode::new("hypi_rapid_script_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_RAPID_SCRIPT_SYNTAX_ERR: ErrorCode =
-        ErrorCode::new("hypi_rapid_script_syntax_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_UNSUPPORTED_PROTOCOL: ErrorCode = ErrorCode::new(
-        "hypi_pipeline_unsupported_protocol",
-        StatusCode::BAD_REQUEST,
-    );
-    pub static ref CODE_PIPELINE_NOT_FOUND: ErrorCode =
-        ErrorCode::new("hypi_pipeline_not_found", StatusCode::INTERNAL_SERVER_ERROR);
-    pub static ref CODE_PIPELINE_EMPTY: ErrorCode =
-        ErrorCode::new("hypi_pipeline_empty", StatusCode::INTERNAL_SERVER_ERROR);
-    pub static ref CODE_PIPELINE_EL_UNSUPPORTED: ErrorCode = ErrorCode::new(
-        "hypi_pipeline_unsupported_element",
-        StatusCode::NOT_IMPLEMENTED,
-    );
-    pub static ref CODE_SCHEMA_INVALID: ErrorCode =
-        ErrorCode::new("hypi_schema_invalid", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SEMANTICS_INVALID_SCHEMA_ROOT: ErrorCode = ErrorCode::new(
-        "hypi_semantics_invalid_schema_root",
-        StatusCode::BAD_REQUEST,
-    );
-    pub static ref CODE_SEMANTICS_DUPLICATE_TABLE: ErrorCode =
-        ErrorCode::new("hypi_semantics_duplicate_table", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SEMANTICS_TABLE_NOT_FOUND: ErrorCode =
-        ErrorCode::new("hypi_semantics_table_not_found", StatusCode::BAD_REQUEST);
+///Carries the error code name on error responses so it's available without parsing the body
+pub const HDR_ERROR_CODE: &str = "x-hypi-error-code";
+///Prefix of a [crate::plugin::Pair] value in error context that holds JSON rather than a plain string.
+/// Context that is anything other than a string or several strings is sent as this prefix followed by the value as JSON,
+/// part of the plugin protocol so it's also documented on `PluginError.context` in proto/rapid_plugin.proto
+pub const CONTEXT_JSON_PREFIX: &str = "\u{1e}json:";
+pub const CODE_UNKNOWN_DOMAIN: ErrorCode = ErrorCode::described(
+    names::UNKNOWN_DOMAIN,
+    StatusCode::NOT_FOUND,
+    "No service is configured for the domain the request was sent to.",
+);
+pub const CODE_FAILED_NO_CONTENT: ErrorCode = ErrorCode::described(
+    names::FAILED_NO_CONTENT,
+    StatusCode::BAD_REQUEST,
+    "The request had no content but the endpoint requires a body.",
+);
+pub const CODE_FAILED_TO_BUILD_RESPONSE: ErrorCode = ErrorCode::described(
+    names::FAILED_TO_BUILD_RESPONSE,
+    StatusCode::BAD_REQUEST,
+    "The response could not be built from the pipeline output.",
+);
+pub const CODE_ENDPOINT_INVALID_REGEX: ErrorCode = ErrorCode::described(
+    names::ENDPOINT_INVALID_REGEX,
+    StatusCode::BAD_REQUEST,
+    "An endpoint path pattern is not a valid regular expression.",
+);
+pub const CODE_MISSING_METHOD: ErrorCode = ErrorCode::described(
+    names::MISSING_METHOD,
+    StatusCode::BAD_REQUEST,
+    "The request has no HTTP method or the method is not supported.",
+);
+pub const CODE_INVALID_METHOD: ErrorCode = ErrorCode::described(
+    names::INVALID_METHOD,
+    StatusCode::BAD_REQUEST,
+    "The request method is not a valid HTTP method.",
+);
+pub const CODE_INVALID_MEDIA_TYPE: ErrorCode = ErrorCode::described(
+    names::INVALID_MEDIA_TYPE,
+    StatusCode::BAD_REQUEST,
+    "The request's Content-Type is not a valid media type.",
+);
+pub const CODE_MISSING_HOST: ErrorCode = ErrorCode::described(
+    names::MISSING_HOST,
+    StatusCode::BAD_REQUEST,
+    "The request has no host so the service could not be resolved.",
+);
+pub const CODE_FS_SCHEMA_FILE_NOT_FOUND: ErrorCode = ErrorCode::described(
+    names::FS_SCHEMA_FILE_NOT_FOUND,
+    StatusCode::BAD_REQUEST,
+    "The schema file configured for the service does not exist.",
+);
+pub const CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED: ErrorCode = ErrorCode::described(
+    names::FS_ABSOLUTE_PATH_NOT_SUPPORTED,
+    StatusCode::BAD_REQUEST,
+    "A file path was absolute, only paths relative to the service are allowed.",
+);
+pub const CODE_FS_DOT_PATHS_NOT_SUPPORTED: ErrorCode = ErrorCode::described(
+    names::FS_DOT_PATHS_NOT_SUPPORTED,
+    StatusCode::BAD_REQUEST,
+    "A file path contained . or .. segments which are not allowed.",
+);
+pub const CODE_FS_FILE_NOT_FOUND: ErrorCode = ErrorCode::described(
+    names::FS_FILE_NOT_FOUND,
+    StatusCode::BAD_REQUEST,
+    "The requested file does not exist.",
+);
+pub const CODE_FS_DOMAIN: ErrorCode = ErrorCode::described(
+    names::FS_DOMAIN,
+    StatusCode::BAD_REQUEST,
+    "No files exist for the domain the request was sent to.",
+);
+pub const CODE_RAPID_SCRIPT_ERR: ErrorCode = ErrorCode::described(
+    names::RAPID_SCRIPT_ERR,
+    StatusCode::BAD_REQUEST,
+    "A RAPID script failed while it was being evaluated.",
+);
+pub const CODE_RAPID_SCRIPT_SYNTAX_ERR: ErrorCode = ErrorCode::described(
+    names::RAPID_SCRIPT_SYNTAX_ERR,
+    StatusCode::BAD_REQUEST,
+    "A RAPID script has invalid syntax, the column of the error is in the context.",
+);
+pub const CODE_UNSUPPORTED_PROTOCOL: ErrorCode = ErrorCode::described(
+    names::UNSUPPORTED_PROTOCOL,
+    StatusCode::BAD_REQUEST,
+    "A pipeline step uses a protocol RAPID does not support.",
+);
+pub const CODE_PIPELINE_NOT_FOUND: ErrorCode = ErrorCode::described(
+    names::PIPELINE_NOT_FOUND,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "The pipeline an endpoint refers to does not exist.",
+);
+pub const CODE_PIPELINE_EMPTY: ErrorCode = ErrorCode::described(
+    names::PIPELINE_EMPTY,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "The pipeline configured for an endpoint has no steps.",
+);
+pub const CODE_PIPELINE_EL_UNSUPPORTED: ErrorCode = ErrorCode::described(
+    names::PIPELINE_EL_UNSUPPORTED,
+    StatusCode::NOT_IMPLEMENTED,
+    "A pipeline contains an element that is not implemented.",
+);
+pub const CODE_SCHEMA_INVALID: ErrorCode = ErrorCode::described(
+    names::SCHEMA_INVALID,
+    StatusCode::BAD_REQUEST,
+    "The service schema is invalid.",
+);
+pub const CODE_SEMANTICS_INVALID_SCHEMA_ROOT: ErrorCode = ErrorCode::described(
+    names::SEMANTICS_INVALID_SCHEMA_ROOT,
+    StatusCode::BAD_REQUEST,
+    "The root of the service schema is not valid.",
+);
+pub const CODE_SEMANTICS_DUPLICATE_TABLE: ErrorCode = ErrorCode::described(
+    names::SEMANTICS_DUPLICATE_TABLE,
+    StatusCode::BAD_REQUEST,
+    "The schema defines the same table more than once.",
+);
+pub const CODE_SEMANTICS_TABLE_NOT_FOUND: ErrorCode = ErrorCode::described(
+    names::SEMANTICS_TABLE_NOT_FOUND,
+    StatusCode::BAD_REQUEST,
+    "The schema refers to a table that is not defined.",
+);
 
-    // pub static ref CODE_JSON_PARSE_ERR: ErrorCode = ErrorCode::new("hypi_json_parse_err",StatusCode::BAD_REQUEST);
-    pub static ref CODE_FS_IO_ERROR: ErrorCode =
-        ErrorCode::new("hypi_io_error", StatusCode::BAD_REQUEST);
-    pub static ref CODE_INVALID_UTF8: ErrorCode =
-        ErrorCode::new("hypi_invalid_utf8", StatusCode::BAD_REQUEST);
-    pub static ref CODE_HTTP_INVALID_HEADER: ErrorCode =
-        ErrorCode::new("hypi_http_invalid_header", StatusCode::BAD_REQUEST);
-    pub static ref CODE_HTTP_INVALID_URI: ErrorCode =
-        ErrorCode::new("hypi_http_invalid_uri", StatusCode::BAD_REQUEST);
-    pub static ref CODE_HTTP_MISSING_HOST: ErrorCode =
-        ErrorCode::new("hypi_http_missing_host", StatusCode::BAD_REQUEST);
-    pub static ref CODE_HTTP_HYPER: ErrorCode =
-        ErrorCode::new("hypi_http_hyper", StatusCode::BAD_REQUEST);
-    pub static ref CODE_HTTP_IO: ErrorCode = ErrorCode::new("hypi_http_io_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_JSON_ERR: ErrorCode = ErrorCode::new("hypi_json_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_UNSUPPORTED: ErrorCode =
-        ErrorCode::new("hypi_sql_unsupported_db_type", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_NO_CONNECTION: ErrorCode =
-        ErrorCode::new("hypi_sql_no_connection", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_UNABLE_TO_CONNECT: ErrorCode =
-        ErrorCode::new("hypi_sql_connection_failed", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_MEKADB_UNABLE_TO_CONNECT: ErrorCode =
-        ErrorCode::new("hypi_sql_mekadb_connection_failed", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_MEKADB_REQ_ERR: ErrorCode =
-        ErrorCode::new("hypi_sql_mekadb_req_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SQL_MEKADB_RES_ERR: ErrorCode =
-        ErrorCode::new("hypi_sql_mekadb_res_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_SCRIPT_ERR: ErrorCode =
-        ErrorCode::new("hypi_script_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_PIPELINE_NO_STEPS: ErrorCode =
-        ErrorCode::new("hypi_pipeline_no_steps", StatusCode::BAD_REQUEST);
-    pub static ref CODE_PIPELINE_DUPLICATE_SEQ_ID: ErrorCode = ErrorCode::new(
-        "hypi_pipeline_duplicate_seq_id",
-        StatusCode::INTERNAL_SERVER_ERROR,
-    );
-    pub static ref CODE_PIPELINE_PLUGIN_BEHAVIOUR: ErrorCode = ErrorCode::new(
-        "hypi_pipeline_bad_behaviour",
-        StatusCode::INTERNAL_SERVER_ERROR,
-    );
-    pub static ref CODE_FORM_FILE_ERR: ErrorCode =
-        ErrorCode::new("hypi_form_file_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FORM_PAYLOAD_ERR: ErrorCode =
-        ErrorCode::new("hypi_form_payload_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_FORM_IO_ERR: ErrorCode =
-        ErrorCode::new("hypi_form_io_err", StatusCode::BAD_REQUEST);
-    pub static ref CODE_DOCKER_ERR: ErrorCode =
-        ErrorCode::new("hypi_docker_err", StatusCode::INTERNAL_SERVER_ERROR);
+// pub const CODE_JSON_PARSE_ERR: ErrorCode = ErrorCode::of("hypi_json_parse_err",StatusCode::BAD_REQUEST);
+pub const CODE_FS_IO_ERROR: ErrorCode = ErrorCode::described(
+    names::FS_IO_ERROR,
+    StatusCode::BAD_REQUEST,
+    "Reading or writing a service file failed.",
+);
+pub const CODE_INVALID_UTF8: ErrorCode = ErrorCode::described(
+    names::INVALID_UTF8,
+    StatusCode::BAD_REQUEST,
+    "Data that must be UTF-8 was not valid UTF-8.",
+);
+pub const CODE_HTTP_INVALID_HEADER: ErrorCode = ErrorCode::described(
+    names::HTTP_INVALID_HEADER,
+    StatusCode::BAD_REQUEST,
+    "The request contains an invalid header.",
+);
+pub const CODE_HTTP_INVALID_URI: ErrorCode = ErrorCode::described(
+    names::HTTP_INVALID_URI,
+    StatusCode::BAD_REQUEST,
+    "The request URI is invalid.",
+);
+pub const CODE_HTTP_MISSING_HOST: ErrorCode = ErrorCode::described(
+    names::HTTP_MISSING_HOST,
+    StatusCode::BAD_REQUEST,
+    "An outbound HTTP request has no host.",
+);
+pub const CODE_HTTP_HYPER: ErrorCode = ErrorCode::classified(
+    names::HTTP_HYPER,
+    StatusCode::BAD_REQUEST,
+    "An outbound HTTP request failed.",
+    ErrorCategory::Dependency,
+    RetryHint::Retryable,
+);
+pub const CODE_HTTP_IO: ErrorCode = ErrorCode::described(
+    names::HTTP_IO,
+    StatusCode::BAD_REQUEST,
+    "An IO error occurred while handling an HTTP request.",
+);
+pub const CODE_JSON_ERR: ErrorCode = ErrorCode::described(
+    names::JSON_ERR,
+    StatusCode::BAD_REQUEST,
+    "JSON could not be parsed or serialised.",
+);
+pub const CODE_SQL_UNSUPPORTED: ErrorCode = ErrorCode::described(
+    names::SQL_UNSUPPORTED,
+    StatusCode::BAD_REQUEST,
+    "The configured database type is not supported.",
+);
+pub const CODE_SQL_NO_CONNECTION: ErrorCode = ErrorCode::classified(
+    names::SQL_NO_CONNECTION,
+    StatusCode::BAD_REQUEST,
+    "No database connection is configured.",
+    ErrorCategory::Server,
+    RetryHint::Fatal,
+);
+pub const CODE_SQL_UNABLE_TO_CONNECT: ErrorCode = ErrorCode::classified(
+    names::SQL_UNABLE_TO_CONNECT,
+    StatusCode::BAD_REQUEST,
+    "Connecting to the database failed.",
+    ErrorCategory::Dependency,
+    RetryHint::Retryable,
+);
+pub const CODE_SQL_MEKADB_UNABLE_TO_CONNECT: ErrorCode = ErrorCode::classified(
+    names::SQL_MEKADB_UNABLE_TO_CONNECT,
+    StatusCode::BAD_REQUEST,
+    "Connecting to MekaDB failed.",
+    ErrorCategory::Dependency,
+    RetryHint::Retryable,
+);
+pub const CODE_SQL_MEKADB_REQ_ERR: ErrorCode = ErrorCode::classified(
+    names::SQL_MEKADB_REQ_ERR,
+    StatusCode::BAD_REQUEST,
+    "A request to MekaDB could not be sent.",
+    ErrorCategory::Dependency,
+    RetryHint::Fatal,
+);
+pub const CODE_SQL_MEKADB_RES_ERR: ErrorCode = ErrorCode::classified(
+    names::SQL_MEKADB_RES_ERR,
+    StatusCode::BAD_REQUEST,
+    "MekaDB returned an error or an invalid response.",
+    ErrorCategory::Dependency,
+    RetryHint::Fatal,
+);
+pub const CODE_SCRIPT_ERR: ErrorCode = ErrorCode::described(
+    names::SCRIPT_ERR,
+    StatusCode::BAD_REQUEST,
+    "A JavaScript step failed.",
+);
+pub const CODE_PIPELINE_NO_STEPS: ErrorCode = ErrorCode::described(
+    names::PIPELINE_NO_STEPS,
+    StatusCode::BAD_REQUEST,
+    "A pipeline was executed without any steps.",
+);
+pub const CODE_PIPELINE_DUPLICATE_SEQ_ID: ErrorCode = ErrorCode::classified(
+    names::PIPELINE_DUPLICATE_SEQ_ID,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "The same sequence id was used for two in-flight plugin requests.",
+    ErrorCategory::Plugin,
+    RetryHint::Fatal,
+);
+pub const CODE_PIPELINE_PLUGIN_BEHAVIOUR: ErrorCode = ErrorCode::classified(
+    names::PIPELINE_PLUGIN_BEHAVIOUR,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "A plugin misbehaved, the plugin name is in the context.",
+    ErrorCategory::Plugin,
+    RetryHint::Fatal,
+);
+pub const CODE_PIPELINE_PLUGIN_INVALID_STATUS: ErrorCode = ErrorCode::classified(
+    names::PIPELINE_PLUGIN_INVALID_STATUS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "A plugin sent a status that is not a valid HTTP status code, the raw value is in the plugin_status context.",
+    ErrorCategory::Plugin,
+    RetryHint::Fatal,
+);
+pub const CODE_PIPELINE_PLUGIN_CONN: ErrorCode = ErrorCode::classified(
+    names::PIPELINE_PLUGIN_CONN,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "RAPID could not send a request to a plugin.",
+    ErrorCategory::Plugin,
+    RetryHint::Retryable,
+);
+pub const CODE_PIPELINE_PLUGIN_TRANSPORT: ErrorCode = ErrorCode::classified(
+    names::PIPELINE_PLUGIN_TRANSPORT,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "RAPID could not reach a plugin over its TCP, Unix domain socket or stdio transport.",
+    ErrorCategory::Plugin,
+    RetryHint::Retryable,
+);
+pub const CODE_FORM_FILE_ERR: ErrorCode = ErrorCode::described(
+    names::FORM_FILE_ERR,
+    StatusCode::BAD_REQUEST,
+    "A file uploaded in a form could not be saved.",
+);
+pub const CODE_FORM_PAYLOAD_ERR: ErrorCode = ErrorCode::described(
+    names::FORM_PAYLOAD_ERR,
+    StatusCode::BAD_REQUEST,
+    "A form payload could not be parsed.",
+);
+pub const CODE_FORM_IO_ERR: ErrorCode = ErrorCode::described(
+    names::FORM_IO_ERR,
+    StatusCode::BAD_REQUEST,
+    "An IO error occurred while reading a form.",
+);
+pub const CODE_DOCKER_ERR: ErrorCode = ErrorCode::classified(
+    names::DOCKER_ERR,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    "Docker failed to run or connect to a plugin container.",
+    ErrorCategory::Dependency,
+    RetryHint::Retryable,
+);
 
+lazy_static! {
         pub static ref GRPC_ERRS: HashMap<Code, ErrorCode> = HashMap::from([
             (
                 Code::Ok,
-                ErrorCode::new("hypi_pipeline_plugin_status_ok", StatusCode::OK)
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_OK,
+                    StatusCode::OK,
+                    "A plugin returned gRPC status OK as an error.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
+                )
             ),
             (
                 Code::Cancelled,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_cancelled",
-                    StatusCode::INTERNAL_SERVER_ERROR
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_CANCELLED,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "A plugin request was cancelled.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Unknown,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_unknown",
-                    StatusCode::INTERNAL_SERVER_ERROR
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_UNKNOWN,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "A plugin failed with an unknown gRPC error.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::InvalidArgument,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_invalidargument",
-                    StatusCode::BAD_REQUEST
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_INVALIDARGUMENT,
+                    StatusCode::BAD_REQUEST,
+                    "A plugin rejected the request as invalid.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::DeadlineExceeded,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_deadlineexceeded",
-                    StatusCode::GATEWAY_TIMEOUT
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_DEADLINEEXCEEDED,
+                    StatusCode::GATEWAY_TIMEOUT,
+                    "A plugin did not respond in time.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Retryable
                 )
             ),
             (
                 Code::NotFound,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_notfound",
-                    StatusCode::NOT_FOUND
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_NOTFOUND,
+                    StatusCode::NOT_FOUND,
+                    "A plugin could not find what was requested.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::AlreadyExists,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_alreadyexists",
-                    StatusCode::CONFLICT
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_ALREADYEXISTS,
+                    StatusCode::CONFLICT,
+                    "A plugin rejected the request because the resource already exists.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::PermissionDenied,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_permissiondenied",
-                    StatusCode::FORBIDDEN
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_PERMISSIONDENIED,
+                    StatusCode::FORBIDDEN,
+                    "A plugin denied permission for the request.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Unauthenticated,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_unauthenticated",
-                    StatusCode::UNAUTHORIZED
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_UNAUTHENTICATED,
+                    StatusCode::UNAUTHORIZED,
+                    "A plugin rejected the request as unauthenticated.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::ResourceExhausted,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_resourceexhausted",
-                    StatusCode::TOO_MANY_REQUESTS
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_RESOURCEEXHAUSTED,
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "A plugin is out of resources or rate limited the request.",
+                    ErrorCategory::Plugin,
+                    RetryHint::RetryAfter(1)
                 )
             ),
             (
                 Code::FailedPrecondition,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_failedprecondition",
-                    StatusCode::PRECONDITION_FAILED
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_FAILEDPRECONDITION,
+                    StatusCode::PRECONDITION_FAILED,
+                    "A plugin rejected the request because a precondition failed.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Aborted,
-                ErrorCode::new("hypi_pipeline_plugin_status_aborted", StatusCode::GONE)
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_ABORTED,
+                    StatusCode::GONE,
+                    "A plugin aborted the request.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Retryable
+                )
             ),
             (
                 Code::OutOfRange,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_outofrange",
-                    StatusCode::RANGE_NOT_SATISFIABLE
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_OUTOFRANGE,
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    "A plugin rejected the request as out of range.",
+                    ErrorCategory::Client,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Unimplemented,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_unimplemented",
-                    StatusCode::NOT_IMPLEMENTED
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_UNIMPLEMENTED,
+                    StatusCode::NOT_IMPLEMENTED,
+                    "A plugin does not implement the request.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Internal,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_internal",
-                    StatusCode::INTERNAL_SERVER_ERROR
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_INTERNAL,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "A plugin failed with an internal error.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
                 )
             ),
             (
                 Code::Unavailable,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_unavailable",
-                    StatusCode::SERVICE_UNAVAILABLE
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_UNAVAILABLE,
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "A plugin is unavailable.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Retryable
                 )
             ),
             (
                 Code::DataLoss,
-                ErrorCode::new(
-                    "hypi_pipeline_plugin_status_dataloss",
-                    StatusCode::INSUFFICIENT_STORAGE
+                ErrorCode::classified(
+                    names::PIPELINE_PLUGIN_STATUS_DATALOSS,
+                    StatusCode::INSUFFICIENT_STORAGE,
+                    "A plugin reported unrecoverable data loss.",
+                    ErrorCategory::Plugin,
+                    RetryHint::Fatal
                 )
             ),
         ]);
     }
+
+///Every error code defined in this module, these are what [crate::registry] starts with
+pub fn error_codes() -> Vec<ErrorCode> {
+    let mut codes = vec![
+        CODE_UNKNOWN_DOMAIN,
+        CODE_FAILED_NO_CONTENT,
+        CODE_FAILED_TO_BUILD_RESPONSE,
+        CODE_ENDPOINT_INVALID_REGEX,
+        CODE_MISSING_METHOD,
+        CODE_INVALID_METHOD,
+        CODE_INVALID_MEDIA_TYPE,
+        CODE_MISSING_HOST,
+        CODE_FS_SCHEMA_FILE_NOT_FOUND,
+        CODE_FS_ABSOLUTE_PATH_NOT_SUPPORTED,
+        CODE_FS_DOT_PATHS_NOT_SUPPORTED,
+        CODE_FS_FILE_NOT_FOUND,
+        CODE_FS_DOMAIN,
+        CODE_RAPID_SCRIPT_ERR,
+        CODE_RAPID_SCRIPT_SYNTAX_ERR,
+        CODE_UNSUPPORTED_PROTOCOL,
+        CODE_PIPELINE_NOT_FOUND,
+        CODE_PIPELINE_EMPTY,
+        CODE_PIPELINE_EL_UNSUPPORTED,
+        CODE_SCHEMA_INVALID,
+        CODE_SEMANTICS_INVALID_SCHEMA_ROOT,
+        CODE_SEMANTICS_DUPLICATE_TABLE,
+        CODE_SEMANTICS_TABLE_NOT_FOUND,
+        CODE_FS_IO_ERROR,
+        CODE_INVALID_UTF8,
+        CODE_HTTP_INVALID_HEADER,
+        CODE_HTTP_INVALID_URI,
+        CODE_HTTP_MISSING_HOST,
+        CODE_HTTP_HYPER,
+        CODE_HTTP_IO,
+        CODE_JSON_ERR,
+        CODE_SQL_UNSUPPORTED,
+        CODE_SQL_NO_CONNECTION,
+        CODE_SQL_UNABLE_TO_CONNECT,
+        CODE_SQL_MEKADB_UNABLE_TO_CONNECT,
+        CODE_SQL_MEKADB_REQ_ERR,
+        CODE_SQL_MEKADB_RES_ERR,
+        CODE_SCRIPT_ERR,
+        CODE_PIPELINE_NO_STEPS,
+        CODE_PIPELINE_DUPLICATE_SEQ_ID,
+        CODE_PIPELINE_PLUGIN_BEHAVIOUR,
+        CODE_PIPELINE_PLUGIN_INVALID_STATUS,
+        CODE_FORM_FILE_ERR,
+        CODE_FORM_PAYLOAD_ERR,
+        CODE_FORM_IO_ERR,
+        CODE_DOCKER_ERR,
+        CODE_PIPELINE_PLUGIN_CONN,
+        CODE_PIPELINE_PLUGIN_TRANSPORT,
+    ];
+    codes.extend(GRPC_ERRS.values().cloned());
+    codes
+}
 /*    pub fn grpc_code2status(code: Code) -> u16 {
         match code {
             Code::Ok => 200,
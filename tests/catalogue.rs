@@ -0,0 +1,28 @@
+use rapid_utils::catalogue::{catalogue, catalogue_json, catalogue_markdown};
+use rapid_utils::wellknown::error_codes;
+
+#[test]
+fn every_well_known_code_is_documented() {
+    let entries = catalogue();
+    for code in error_codes() {
+        let entry = entries.iter().find(|e| e.name == code.name()).unwrap();
+        assert!(!entry.description.is_empty(), "{} has no description", code);
+        assert_eq!(entry.http_status, code.http_status.as_u16());
+    }
+}
+
+#[test]
+fn renders_json_and_markdown() {
+    let json: serde_json::Value = serde_json::from_str(&catalogue_json()).unwrap();
+    let missing_host = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["name"] == "hypi_missing_host")
+        .unwrap();
+    assert_eq!(missing_host["http_status"], 400);
+    assert_eq!(missing_host["category"], "client");
+    assert_eq!(missing_host["retry"], "fatal");
+    assert!(catalogue_markdown().contains("| `hypi_missing_host` | 400 | client | fatal |"));
+    assert!(catalogue_markdown().contains("| `hypi_pipeline_plugin_status_resourceexhausted` | 429 | plugin | after 1s |"));
+}
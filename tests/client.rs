@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod common;
+
+use common::Echo;
+use rapid_utils::client::PluginClient;
+use rapid_utils::err::PipelineError;
+use rapid_utils::plugin::rapid_plugin_server::RapidPlugin as RapidPluginService;
+use rapid_utils::plugin::{output_sequence, InputSequence, OutputSequence, PluginResponse};
+use rapid_utils::sdk::PluginService;
+use rapid_utils::transport::in_memory;
+use rapid_utils::wellknown::CODE_PIPELINE_NOT_FOUND;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+
+///Answers every request twice and also answers a request that was never sent
+struct Chatty;
+
+#[tonic::async_trait]
+impl RapidPluginService for Chatty {
+    type ExecuteStream = ReceiverStream<Result<OutputSequence, Status>>;
+
+    async fn execute(
+        &self,
+        request: Request<Streaming<InputSequence>>,
+    ) -> Result<Response<Self::ExecuteStream>, Status> {
+        let mut inputs = request.into_inner();
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        tokio::spawn(async move {
+            while let Some(Ok(input)) = inputs.next().await {
+                let response = PluginResponse { status: 200, headers: vec![], body: input.body };
+                for id in [input.id, input.id, 999] {
+                    let value = Some(output_sequence::Value::Response(response.clone()));
+                    let _ = tx.send(Ok(OutputSequence { id, value })).await;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+fn input(body: &str) -> InputSequence {
+    InputSequence { body: body.as_bytes().to_vec(), ..Default::default() }
+}
+
+#[tokio::test]
+async fn routes_responses_to_callers() {
+    let client = PluginClient::new("test", in_memory(PluginService::new(Echo))).await.unwrap();
+    let bodies = ["a", "bb", "ccc", "dddd"];
+    let responses = futures_util::future::join_all(bodies.iter().map(|b| client.execute(input(b)))).await;
+    for (body, response) in bodies.iter().zip(responses) {
+        assert_eq!(response.unwrap().body, body.as_bytes());
+    }
+    match client.execute(input("missing")).await {
+        Err(PipelineError::PluginSeqErr(e)) => assert_eq!(e.code, CODE_PIPELINE_NOT_FOUND.name()),
+        other => panic!("expected a plugin error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn reports_duplicate_and_unknown_ids() {
+    let errors = Arc::new(Mutex::new(vec![]));
+    let handler = {
+        let errors = errors.clone();
+        Arc::new(move |e: PipelineError| errors.lock().unwrap().push(e))
+    };
+    let client = PluginClient::with_error_handler("chatty", in_memory(Chatty), handler).await.unwrap();
+    assert_eq!(client.execute(input("hi")).await.unwrap().body, b"hi");
+    while errors.lock().unwrap().len() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let errors = errors.lock().unwrap();
+    assert!(matches!(errors[0], PipelineError::DuplicateSequenceId));
+    assert!(matches!(&errors[1], PipelineError::PluginErr(_, plugin) if plugin == "chatty"));
+}
+
+#[tokio::test]
+async fn callers_that_give_up_are_forgotten() {
+    let client = PluginClient::new("test", in_memory(PluginService::new(Echo))).await.unwrap();
+    //Echo takes 49ms to answer "a"
+    assert!(tokio::time::timeout(Duration::from_millis(5), client.execute(input("a"))).await.is_err());
+    assert_eq!(client.in_flight(), 0);
+    assert_eq!(client.execute(input("b")).await.unwrap().body, b"b");
+}
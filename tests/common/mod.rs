@@ -0,0 +1,26 @@
+//!Fixtures shared by the integration tests
+
+use std::time::Duration;
+
+use rapid_utils::err::HttpError;
+use rapid_utils::plugin::{InputSequence, PluginResponse};
+use rapid_utils::sdk::RapidPlugin;
+use rapid_utils::wellknown::CODE_PIPELINE_NOT_FOUND;
+
+///Responds with the request body. A body of `missing` fails with [CODE_PIPELINE_NOT_FOUND] and `panic` panics.
+/// Short bodies take longer so concurrent requests finish out of order and responses have to be routed by id
+pub struct Echo;
+
+#[tonic::async_trait]
+impl RapidPlugin for Echo {
+    async fn handle(&self, request: InputSequence) -> Result<PluginResponse, HttpError> {
+        match request.body.as_slice() {
+            b"missing" => Err(HttpError::new(&CODE_PIPELINE_NOT_FOUND).message("No such pipeline").with("id", 1).build()),
+            b"panic" => panic!("handler bug"),
+            body => {
+                tokio::time::sleep(Duration::from_millis(50u64.saturating_sub(body.len() as u64))).await;
+                Ok(PluginResponse { status: 200, headers: vec![], body: body.to_vec() })
+            }
+        }
+    }
+}
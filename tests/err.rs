@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+
+use http::StatusCode;
+use rapid_utils::err::{ErrorCategory, ErrorCode, HttpError, OutputSequenceError, PipelineError, RetryHint};
+use rapid_utils::plugin::{output_sequence, OutputSequence, Pair, PluginError, PluginResponse};
+use rapid_utils::wellknown::{
+    names, CODE_DOCKER_ERR, CODE_MISSING_HOST, CODE_PIPELINE_PLUGIN_BEHAVIOUR, CODE_PIPELINE_PLUGIN_INVALID_STATUS,
+    CONTEXT_JSON_PREFIX,
+};
+use serde_json::json;
+
+fn plugin_err(status: i32) -> PluginError {
+    PluginError {
+        status,
+        code: "my_plugin_err".to_string(),
+        message: "it broke".to_string(),
+        context: vec![
+            Pair { key: "a".to_string(), value: vec!["1".to_string()] },
+            Pair { key: "b".to_string(), value: vec!["x".to_string(), "y".to_string()] },
+        ],
+    }
+}
+
+#[test]
+fn output_sequence_to_http_error() {
+    let seq = OutputSequence {
+        id: 3,
+        value: Some(output_sequence::Value::Error(plugin_err(409))),
+    };
+    let err = HttpError::try_from(seq).unwrap();
+    assert_eq!(err.code.name, "my_plugin_err");
+    assert_eq!(err.code.http_status.as_u16(), 409);
+    assert_eq!(err.message, "it broke");
+    assert_eq!(err.context_str("a"), Some("1"));
+    assert_eq!(err.context_value("b"), Some(&json!(["x", "y"])));
+}
+
+#[test]
+fn output_sequence_not_an_error() {
+    let seq = OutputSequence {
+        id: 4,
+        value: Some(output_sequence::Value::Response(PluginResponse::default())),
+    };
+    assert!(matches!(HttpError::try_from(seq), Err(OutputSequenceError::NotAnError(4))));
+    let seq = OutputSequence { id: 5, value: None };
+    assert!(matches!(HttpError::try_from(seq), Err(OutputSequenceError::Empty(5))));
+}
+
+#[test]
+fn plugin_error_with_invalid_status() {
+    for status in [0, 1, 99, 1000, -1, -500, i32::MAX, i32::MIN, 65536 + 200] {
+        let err = HttpError::from(plugin_err(status));
+        assert_eq!(err.code.name, CODE_PIPELINE_PLUGIN_INVALID_STATUS.name, "status {}", status);
+        assert_eq!(err.code.http_status.as_u16(), 500);
+        assert_eq!(err.context_value("plugin_status"), Some(&json!(status)));
+        assert_eq!(err.context_str("plugin_code"), Some("my_plugin_err"));
+        assert_eq!(err.context_str("a"), Some("1"));
+    }
+}
+
+#[test]
+fn plugin_error_with_edge_valid_status() {
+    for status in [100, 200, 599, 999] {
+        let err: HttpError = PipelineError::PluginSeqErr(plugin_err(status)).into();
+        assert_eq!(err.code.name, "my_plugin_err");
+        assert_eq!(err.code.http_status.as_u16() as i32, status);
+    }
+}
+
+#[test]
+fn every_grpc_code_maps_to_an_error() {
+    for code in 0..=20 {
+        let status = tonic::Status::new(tonic::Code::from_i32(code), "m");
+        let err: HttpError = PipelineError::PluginStatusErr(status).into();
+        assert!(err.code.name.starts_with("hypi_pipeline_plugin_status_"));
+    }
+}
+
+#[test]
+fn typed_context_round_trips_through_output_sequence() {
+    let err = HttpError::from(plugin_err(400))
+        .with_context("count", 2)
+        .with_context("nested", json!({"k": "v"}))
+        .with_context("one", json!(["x"]))
+        .with_context("mixed", json!(["x", 1, null]))
+        .with_context("none", json!([]))
+        .with_context("prefixed", format!("{}2", CONTEXT_JSON_PREFIX));
+    let seq = OutputSequence::from(err);
+    let err = HttpError::try_from(seq).unwrap();
+    assert_eq!(err.context_value("count"), Some(&json!(2)));
+    assert_eq!(err.context_value("b"), Some(&json!(["x", "y"])));
+    assert_eq!(err.context_value("nested"), Some(&json!({"k": "v"})));
+    assert_eq!(err.context_value("one"), Some(&json!(["x"])));
+    assert_eq!(err.context_value("mixed"), Some(&json!(["x", 1, null])));
+    assert_eq!(err.context_value("none"), Some(&json!([])));
+    assert_eq!(err.context_str("prefixed"), Some(format!("{}2", CONTEXT_JSON_PREFIX).as_str()));
+}
+
+#[test]
+fn plain_string_context_is_sent_as_is() {
+    let err = HttpError::new(&CODE_MISSING_HOST).build().with_context("s", "v").with_context("many", json!(["x", "y"]));
+    let Some(output_sequence::Value::Error(e)) = OutputSequence::from(err).value else {
+        panic!("not an error");
+    };
+    let values = |key: &str| e.context.iter().find(|p| p.key == key).map(|p| p.value.clone());
+    assert_eq!(values("s"), Some(vec!["v".to_string()]));
+    assert_eq!(values("many"), Some(vec!["x".to_string(), "y".to_string()]));
+}
+
+#[test]
+fn string_context_serialises_as_before() {
+    let err = HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR)
+        .message("m")
+        .build()
+        .with_context("plugin", "p1");
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        json!({
+            "code": "hypi_pipeline_bad_behaviour",
+            "message": "m",
+            "context": {"plugin": "p1"},
+            "category": "plugin",
+            "retry": "fatal"
+        })
+    );
+}
+
+#[test]
+fn builder_sets_message_and_context() {
+    let err = HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR)
+        .message_fmt(format_args!("Plugin {} failed", "p1"))
+        .with("attempts", 3)
+        .build();
+    assert_eq!(err.message, "Plugin p1 failed");
+    assert_eq!(err.context_value("attempts"), Some(&json!(3)));
+    let err = HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR).build();
+    assert!(err.context.is_none());
+}
+
+#[test]
+fn source_is_kept_but_not_serialised() {
+    let io = std::io::Error::other("disk on fire");
+    let err = HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR)
+        .message("Internal error.")
+        .source(io)
+        .build();
+    assert_eq!(std::error::Error::source(&err).unwrap().to_string(), "disk on fire");
+    assert_eq!(
+        err.chain().to_string(),
+        "hypi_pipeline_bad_behaviour: Internal error. Caused by: disk on fire"
+    );
+    assert!(!err.to_string().contains("disk on fire"));
+    //clones made from the cache keep the cause
+    assert!(std::error::Error::source(&HttpError::from(&err)).is_some());
+}
+
+#[test]
+fn well_known_codes_are_const() {
+    const CODE: ErrorCode = CODE_MISSING_HOST;
+    assert!(matches!(CODE.name, Cow::Borrowed("hypi_missing_host")));
+    assert_eq!(ErrorCode::new("hypi_missing_host", StatusCode::BAD_REQUEST), CODE);
+    let code = HttpError::new(&CODE_MISSING_HOST).build().code;
+    assert_eq!(code, CODE_MISSING_HOST);
+}
+
+#[test]
+fn well_known_names_are_patterns() {
+    let describe = |err: &HttpError| match err.code.name() {
+        names::MISSING_HOST => "no host",
+        names::PIPELINE_PLUGIN_STATUS_NOTFOUND => "plugin not found",
+        _ => "other",
+    };
+    assert_eq!(describe(&HttpError::new(&CODE_MISSING_HOST).build()), "no host");
+    assert_eq!(describe(&HttpError::from(tonic::Status::not_found("nope"))), "plugin not found");
+    assert_eq!(describe(&HttpError::new(&CODE_DOCKER_ERR).build()), "other");
+}
+
+#[test]
+fn categories_and_retry_hints() {
+    let err = HttpError::from(tonic::Status::resource_exhausted("slow down"));
+    assert_eq!(err.code.category(), ErrorCategory::Plugin);
+    assert_eq!(err.code.retry(), RetryHint::RetryAfter(1));
+    let body = serde_json::to_value(&err).unwrap();
+    assert_eq!(body["retry"], "retry_after");
+    assert_eq!(body["retry_after"], 1);
+    assert!(CODE_DOCKER_ERR.retry().is_retryable());
+    assert_eq!(CODE_DOCKER_ERR.category(), ErrorCategory::Dependency);
+    assert!(!CODE_MISSING_HOST.retry().is_retryable());
+    //codes without a category or hint get one from their status
+    assert_eq!(ErrorCode::new("x", StatusCode::SERVICE_UNAVAILABLE).retry(), RetryHint::Retryable);
+    assert_eq!(ErrorCode::new("x", StatusCode::NOT_FOUND).category(), ErrorCategory::Client);
+    assert_eq!(ErrorCode::new("x", StatusCode::INTERNAL_SERVER_ERROR).category(), ErrorCategory::Server);
+}
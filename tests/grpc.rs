@@ -0,0 +1,58 @@
+use rapid_utils::err::{HttpError, PipelineError};
+use rapid_utils::wellknown::{CODE_MISSING_HOST, HDR_ERROR_CODE};
+use serde_json::json;
+use tonic::{Code, Status};
+
+#[test]
+fn http_error_round_trips_through_status() {
+    let err = HttpError::new(&CODE_MISSING_HOST)
+        .message("No host")
+        .with("hosts", json!(["a", "b"]))
+        .build();
+    let status = Status::from(err);
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "No host");
+    assert_eq!(status.metadata().get(HDR_ERROR_CODE).unwrap(), "hypi_missing_host");
+    let err = HttpError::from(status);
+    assert_eq!(err.code.name, "hypi_missing_host");
+    assert_eq!(err.code.http_status.as_u16(), 400);
+    assert_eq!(err.message, "No host");
+    assert_eq!(err.context_value("hosts"), Some(&json!(["a", "b"])));
+    assert_eq!(err.code.description, CODE_MISSING_HOST.description);
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn native_status_uses_grpc_codes() {
+    let err = HttpError::from(Status::unavailable("try later"));
+    assert_eq!(err.code.name, "hypi_pipeline_plugin_status_unavailable");
+    assert_eq!(err.code.http_status.as_u16(), 503);
+    assert_eq!(err.message, "try later");
+    let err: HttpError = PipelineError::PluginStatusErr(Status::not_found("nope")).into();
+    assert_eq!(err.code.http_status.as_u16(), 404);
+}
+
+#[test]
+fn foreign_status_details_are_ignored() {
+    //a google.rpc.Status { code: 5, message: "nope" } which also decodes as a PluginError
+    let details = vec![0x08, 0x05, 0x12, 0x04, b'n', b'o', b'p', b'e'];
+    let err = HttpError::from(Status::with_details(Code::NotFound, "nope", details.into()));
+    assert_eq!(err.code.name, "hypi_pipeline_plugin_status_notfound");
+    assert_eq!(err.code.http_status.as_u16(), 404);
+    assert_eq!(err.message, "nope");
+    assert!(err.context_value("plugin_code").is_none());
+}
+
+#[test]
+fn errors_never_become_grpc_ok() {
+    let err = HttpError::from(rapid_utils::plugin::PluginError {
+        status: 200,
+        code: "my_plugin_err".to_string(),
+        ..Default::default()
+    });
+    let status = Status::from(err);
+    assert_eq!(status.code(), Code::Unknown);
+    let err = HttpError::from(status);
+    assert_eq!(err.code.name, "my_plugin_err");
+    assert_eq!(err.code.http_status.as_u16(), 200);
+}
@@ -0,0 +1,170 @@
+use bytes::Bytes;
+use http::{HeaderName, Response};
+use http_body_util::Full;
+use rapid_utils::err::HttpError;
+use rapid_utils::http_utils::{Accept, ExtensionMethod, HttpMethod, MediaType, MAX_EXTENSION_METHOD_LEN};
+use rapid_utils::problem::ErrorFormat;
+use rapid_utils::wellknown::{
+    APPLICATION_JSON_HDR, APPLICATION_PROBLEM_JSON_HDR, CODE_INVALID_MEDIA_TYPE, CODE_INVALID_METHOD, CODE_MISSING_HOST,
+    CODE_MISSING_METHOD, FORM_URL_ENCODED_HDR, HDR_CONTENT_TYPE, HDR_ERROR_CODE, MULTIPART_FORM_DATA_HDR,
+};
+
+#[test]
+fn http_error_to_response() {
+    let res: Response<Bytes> = HttpError::new(&CODE_MISSING_HOST).message("No host").build().into();
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.headers()[HDR_CONTENT_TYPE], APPLICATION_JSON_HDR);
+    assert_eq!(res.headers()[HDR_ERROR_CODE], "hypi_missing_host");
+    assert!(String::from_utf8_lossy(res.body()).contains("No host"));
+    let _: Response<Full<Bytes>> = HttpError::new(&CODE_MISSING_HOST).build().into();
+}
+
+#[test]
+fn http_error_to_problem_response_without_code_header() {
+    let res = HttpError::new(&CODE_MISSING_HOST).build().to_response(ErrorFormat::Problem, None);
+    assert_eq!(res.headers()[HDR_CONTENT_TYPE], APPLICATION_PROBLEM_JSON_HDR);
+    assert!(res.headers().get(HDR_ERROR_CODE).is_none());
+}
+
+#[test]
+fn http_error_to_response_with_custom_code_header() {
+    let name = HeaderName::from_bytes(b"X-Error").unwrap();
+    let res = HttpError::new(&CODE_MISSING_HOST).build().to_response(ErrorFormat::Hypi, Some(&name));
+    assert_eq!(res.headers()["x-error"], "hypi_missing_host");
+}
+
+#[test]
+fn retry_after_header() {
+    let res: Response<Bytes> = HttpError::from(tonic::Status::resource_exhausted("slow down")).into();
+    assert_eq!(res.headers()[http::header::RETRY_AFTER], "1");
+}
+
+#[test]
+fn parse_http_method() {
+    assert_eq!("get".parse::<HttpMethod>().unwrap(), HttpMethod::Get);
+    assert_eq!(HttpMethod::try_from("Patch").unwrap(), HttpMethod::Patch);
+    assert_eq!(
+        "PROPFIND".parse::<HttpMethod>().unwrap(),
+        HttpMethod::Extension(ExtensionMethod::new("PROPFIND").unwrap())
+    );
+    assert_eq!(HttpMethod::try_from(&http::Method::from_bytes(b"QUERY").unwrap()).unwrap().to_string(), "QUERY");
+    assert_eq!("X".repeat(32).parse::<HttpMethod>().unwrap_err().code, CODE_INVALID_METHOD);
+    assert_eq!("".parse::<HttpMethod>().unwrap_err().code, CODE_MISSING_METHOD);
+    assert_eq!("GE T".parse::<HttpMethod>().unwrap_err().code, CODE_INVALID_METHOD);
+    #[allow(deprecated)]
+    {
+        assert_eq!(HttpMethod::from("post"), Some(HttpMethod::Post));
+        assert_eq!(HttpMethod::from("GE T"), None);
+    }
+    //valid RFC 9110 tokens that http::Method can't represent
+    for invalid in ["#X", "A$B", "100%", "A&B", "IT'S"] {
+        assert_eq!(invalid.parse::<HttpMethod>().unwrap_err().code, CODE_INVALID_METHOD, "{}", invalid);
+    }
+}
+
+#[test]
+fn http_method_serde_and_routing_keys() {
+    let methods: Vec<HttpMethod> = serde_json::from_str(r#"["get", "Post", "PROPFIND"]"#).unwrap();
+    assert_eq!(serde_json::to_string(&methods).unwrap(), r#"["GET","POST","PROPFIND"]"#);
+    assert!(serde_json::from_str::<HttpMethod>(r#""not valid""#).is_err());
+    let routes = std::collections::HashMap::from([(HttpMethod::Get, "list"), (methods[2], "props")]);
+    assert_eq!(routes[&"PROPFIND".parse().unwrap()], "props");
+    let mut sorted = vec![HttpMethod::Patch, HttpMethod::Options, HttpMethod::Get];
+    sorted.sort();
+    assert_eq!(sorted, vec![HttpMethod::Options, HttpMethod::Get, HttpMethod::Patch]);
+}
+
+#[test]
+fn http_method_semantics() {
+    assert!(HttpMethod::Get.is_safe() && HttpMethod::Get.is_cacheable() && !HttpMethod::Get.allows_request_body());
+    assert!(!HttpMethod::Put.is_safe() && HttpMethod::Put.is_idempotent() && HttpMethod::Put.allows_request_body());
+    assert!(!HttpMethod::Post.is_idempotent() && !HttpMethod::Post.is_cacheable());
+    assert!(HttpMethod::Delete.is_idempotent() && !HttpMethod::Delete.allows_request_body());
+    assert!(!HttpMethod::Trace.allows_request_body());
+    let propfind: HttpMethod = "PROPFIND".parse().unwrap();
+    assert!(!propfind.is_safe() && !propfind.is_idempotent() && propfind.allows_request_body());
+}
+
+#[test]
+fn allow_header_is_sorted_and_deduplicated() {
+    let allow = HttpMethod::allow_header([HttpMethod::Post, HttpMethod::Get, HttpMethod::Head, HttpMethod::Get]);
+    assert_eq!(allow, "GET, POST, HEAD");
+    assert_eq!(HttpMethod::allow_header([]), "");
+}
+
+#[test]
+fn http_method_round_trips_through_http_method() {
+    let mut methods = vec![
+        HttpMethod::Options,
+        HttpMethod::Get,
+        HttpMethod::Post,
+        HttpMethod::Put,
+        HttpMethod::Delete,
+        HttpMethod::Head,
+        HttpMethod::Trace,
+        HttpMethod::Connect,
+        HttpMethod::Patch,
+    ];
+    //every token char at every position and every length up to the limit
+    let tchars = "!#$%&'*+-.^_`|~0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    for (i, c) in tchars.chars().enumerate() {
+        let len = i % MAX_EXTENSION_METHOD_LEN + 1;
+        for name in [format!("{}{}", c, "X".repeat(len - 1)), format!("{}{}", "Y".repeat(len - 1), c)] {
+            let parsed = name.parse::<HttpMethod>();
+            assert_eq!(parsed.is_ok(), http::Method::from_bytes(name.as_bytes()).is_ok(), "{}", name);
+            methods.extend(parsed);
+        }
+    }
+    for method in methods {
+        let http_method = http::Method::from(method);
+        assert_eq!(http_method.as_str(), method.as_str());
+        assert_eq!(http::Method::from(&method), http_method);
+        assert_eq!(HttpMethod::try_from(&http_method).unwrap(), method);
+    }
+    for method in [http::Method::GET, http::Method::from_bytes(b"PROPFIND").unwrap()] {
+        assert_eq!(http::Method::from(HttpMethod::try_from(&method).unwrap()), method);
+    }
+    let too_long = http::Method::from_bytes("X".repeat(MAX_EXTENSION_METHOD_LEN + 1).as_bytes()).unwrap();
+    assert_eq!(HttpMethod::try_from(&too_long).unwrap_err().code, CODE_INVALID_METHOD);
+}
+
+#[test]
+fn media_type_parsing() {
+    let json: MediaType = "Application/JSON; Charset=UTF-8".parse().unwrap();
+    assert_eq!((json.ty(), json.subtype(), json.suffix()), ("application", "json", None));
+    assert_eq!(json.charset(), Some("UTF-8"));
+    assert!(json.is_json() && !json.is_problem_json());
+
+    let problem: MediaType = APPLICATION_PROBLEM_JSON_HDR.parse().unwrap();
+    assert_eq!((problem.subtype(), problem.suffix()), ("problem", Some("json")));
+    assert!(problem.is_json() && problem.is_problem_json());
+
+    let multipart: MediaType = r#"multipart/form-data; boundary="a;b \"c\"""#.parse().unwrap();
+    assert!(multipart.is_multipart() && !multipart.is_form());
+    assert_eq!(multipart.boundary(), Some(r#"a;b "c""#));
+    assert_eq!(multipart.essence(), MULTIPART_FORM_DATA_HDR);
+    assert_eq!(multipart.to_string().parse::<MediaType>().unwrap(), multipart);
+    assert!(FORM_URL_ENCODED_HDR.parse::<MediaType>().unwrap().is_form());
+
+    for invalid in ["", "json", "application/", "a b/c", "text/plain; charset", r#"text/plain; a="b"#] {
+        assert_eq!(invalid.parse::<MediaType>().unwrap_err().code, CODE_INVALID_MEDIA_TYPE, "{}", invalid);
+    }
+}
+
+#[test]
+fn accept_negotiation() {
+    let available = [APPLICATION_JSON_HDR, APPLICATION_PROBLEM_JSON_HDR, FORM_URL_ENCODED_HDR];
+    assert_eq!(Accept::parse("").negotiate(&available), Some(APPLICATION_JSON_HDR));
+    assert_eq!(Accept::parse("*/*").negotiate(&available), Some(APPLICATION_JSON_HDR));
+    let accept = Accept::parse("application/*;q=0.5, application/problem+json, text/html;q=0");
+    assert_eq!(accept.negotiate(&available), Some(APPLICATION_PROBLEM_JSON_HDR));
+    assert_eq!(accept.quality(&"application/json".parse().unwrap()), 0.5);
+    assert_eq!(accept.quality(&"text/html".parse().unwrap()), 0.0);
+    //the most specific range wins even when it has a lower q-value
+    let accept = Accept::parse("*/*, application/json;q=0.1, bogus, text/plain;q=2");
+    assert_eq!(accept.ranges().count(), 2);
+    assert_eq!(accept.negotiate(&available), Some(APPLICATION_PROBLEM_JSON_HDR));
+    assert_eq!(Accept::parse("text/html").negotiate(&available), None);
+    assert_eq!(ErrorFormat::negotiate(&Accept::parse("application/problem+json")), ErrorFormat::Problem);
+    assert_eq!(ErrorFormat::negotiate(&Accept::parse("*/*")), ErrorFormat::Hypi);
+}
@@ -0,0 +1,72 @@
+use bytes::Bytes;
+use rapid_utils::err::{HttpError, PipelineError, RapidScriptError};
+use rapid_utils::i18n::{parse_accept_language, MessageCatalogue};
+use rapid_utils::redact::REDACTED_MESSAGE;
+use rapid_utils::wellknown::{
+    CODE_DOCKER_ERR, CODE_PIPELINE_EMPTY, CODE_PIPELINE_PLUGIN_BEHAVIOUR, CODE_RAPID_SCRIPT_SYNTAX_ERR,
+};
+
+fn catalogue() -> MessageCatalogue {
+    let mut catalogue = MessageCatalogue::new()
+        .with("en", &CODE_PIPELINE_PLUGIN_BEHAVIOUR, "Plugin {plugin} failed: {message}")
+        .with("fr", &CODE_PIPELINE_PLUGIN_BEHAVIOUR, "Le plugin {plugin} a échoué");
+    catalogue
+        .add_json("pt-BR", r#"{"hypi_pipeline_bad_behaviour": "O plugin {plugin} falhou {unknown}"}"#)
+        .unwrap();
+    catalogue
+}
+
+fn plugin_err() -> HttpError {
+    PipelineError::PluginErr("bad output".to_string(), "p1".to_string()).into()
+}
+
+#[test]
+fn picks_language_by_q_value() {
+    let c = catalogue();
+    assert_eq!(c.localise(&plugin_err(), Some("de;q=0.9, fr;q=0.8, en;q=0.5")), "Le plugin p1 a échoué");
+    assert_eq!(c.localise(&plugin_err(), Some("pt-BR")), "O plugin p1 falhou {unknown}");
+    assert_eq!(c.localise(&plugin_err(), Some("fr-CA")), "Le plugin p1 a échoué");
+}
+
+#[test]
+fn falls_back_to_english_then_the_original_message() {
+    let c = catalogue();
+    assert_eq!(c.localise(&plugin_err(), Some("de")), "Plugin p1 failed: bad output");
+    assert_eq!(c.localise(&plugin_err(), None), "Plugin p1 failed: bad output");
+    let empty: HttpError = PipelineError::EmptyPipeline.into();
+    assert_eq!(
+        empty.localised(&c, Some("fr")).message,
+        "Server error, configured pipeline has no steps to execute."
+    );
+    assert_eq!(empty.localised(&c, Some("fr")).code, CODE_PIPELINE_EMPTY);
+}
+
+#[test]
+fn parses_accept_language() {
+    assert_eq!(
+        parse_accept_language("en-GB, fr;q=0.5, *;q=0.1, de;q=0"),
+        vec![("en-gb".to_string(), 1.0), ("fr".to_string(), 0.5)]
+    );
+}
+
+#[test]
+fn hidden_context_keys_are_not_interpolated() {
+    let c = MessageCatalogue::new().with("en", &CODE_RAPID_SCRIPT_SYNTAX_ERR, "Syntax error in {script} at {column}");
+    let err: HttpError = PipelineError::RapidScript(RapidScriptError::SyntaxErr {
+        msg: "Unexpected token".to_string(),
+        pos: 7,
+        exp: "secret + 1".to_string(),
+    })
+    .into();
+    assert_eq!(err.localised(&c, None).message, "Syntax error in {script} at 7");
+}
+
+#[test]
+fn redacted_messages_are_translated() {
+    let c = MessageCatalogue::new().with("fr", &CODE_DOCKER_ERR, "Erreur interne ({message}).");
+    let err = HttpError::new(&CODE_DOCKER_ERR).message("connection to 10.0.0.3 refused").build();
+    let localised = err.localised(&c, Some("fr"));
+    assert_eq!(localised.message, format!("Erreur interne ({}).", REDACTED_MESSAGE));
+    let body: serde_json::Value = serde_json::from_slice(&Bytes::from(&localised)).unwrap();
+    assert_eq!(body["message"], localised.message.as_str());
+}
@@ -0,0 +1,55 @@
+use http::HeaderMap;
+use rapid_utils::http_utils::HttpMethod;
+use rapid_utils::meta::{RequestMeta, RequestMetaBuilder};
+use rapid_utils::plugin::{InputSequence, Pair};
+use rapid_utils::wellknown::{CODE_INVALID_METHOD, CODE_MISSING_METHOD, META_HTTP_ENDPOINT_NAME, META_HTTP_METHOD};
+
+#[test]
+fn builder_and_view_agree() {
+    let mut headers = HeaderMap::new();
+    headers.append("accept", "application/json".parse().unwrap());
+    headers.append("accept", "text/plain".parse().unwrap());
+    let input = InputSequence {
+        body: b"{}".to_vec(),
+        ..RequestMetaBuilder::new()
+            .method("PROPFIND".parse().unwrap())
+            .endpoint("create-user")
+            .meta("trace", "abc")
+            .headers(&headers)
+            .header("X-Tenant", "t1")
+            .build()
+    };
+    let meta = input.request_meta();
+    assert_eq!(meta.method().unwrap().as_str(), "PROPFIND");
+    assert_eq!(meta.endpoint(), Some("create-user"));
+    assert_eq!(meta.meta("trace"), Some("abc"));
+    assert_eq!(meta.header("Accept"), Some("application/json"));
+    assert_eq!(meta.header_values("ACCEPT").collect::<Vec<_>>(), vec!["application/json", "text/plain"]);
+    assert_eq!(meta.header("x-tenant"), Some("t1"));
+    let map = meta.header_map();
+    assert_eq!(map.get_all("accept").iter().count(), 2);
+    assert_eq!(map["x-tenant"], "t1");
+    assert_eq!(input.headers.iter().filter(|p| p.key == "accept").count(), 1);
+}
+
+#[test]
+fn missing_and_invalid_methods() {
+    let input = InputSequence::default();
+    let meta = RequestMeta::new(&input);
+    assert_eq!(meta.method().unwrap_err().code, CODE_MISSING_METHOD);
+    assert_eq!(meta.method_or_get().unwrap(), HttpMethod::Get);
+    assert_eq!(meta.endpoint(), None);
+
+    let input = InputSequence {
+        meta: vec![
+            Pair { key: META_HTTP_METHOD.to_string(), value: vec!["not valid".to_string()] },
+            Pair { key: META_HTTP_ENDPOINT_NAME.to_string(), value: vec![] },
+        ],
+        ..Default::default()
+    };
+    let meta = input.request_meta();
+    assert_eq!(meta.method().unwrap_err().code, CODE_INVALID_METHOD);
+    assert_eq!(meta.method_or_get().unwrap_err().code, CODE_INVALID_METHOD);
+    assert_eq!(meta.endpoint(), None);
+    assert_eq!(RequestMetaBuilder::new().method(HttpMethod::Post).method(HttpMethod::Put).build().meta.len(), 1);
+}
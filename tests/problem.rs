@@ -0,0 +1,32 @@
+use rapid_utils::err::{HttpError, PipelineError};
+use rapid_utils::problem::ErrorFormat;
+use rapid_utils::wellknown::{APPLICATION_PROBLEM_JSON_HDR, CODE_PIPELINE_PLUGIN_BEHAVIOUR};
+use serde_json::{json, Value};
+
+#[test]
+fn renders_problem_json() {
+    let err: HttpError = PipelineError::PluginErr("Plugin sent garbage".to_string(), "p1".to_string()).into();
+    let err = err.with_context("status", "ignored");
+    assert_eq!(
+        err.to_problem(),
+        json!({
+            "type": "hypi_pipeline_bad_behaviour",
+            "title": "Internal Server Error",
+            "status": 500,
+            "detail": "Plugin sent garbage",
+            "category": "plugin",
+            "retry": "fatal",
+            "plugin": "p1"
+        })
+    );
+}
+
+#[test]
+fn body_format_is_selectable() {
+    let err = HttpError::new(&CODE_PIPELINE_PLUGIN_BEHAVIOUR).message("m").build();
+    let problem: Value = serde_json::from_slice(&err.to_body(ErrorFormat::Problem)).unwrap();
+    assert_eq!(problem["detail"], "m");
+    let hypi: Value = serde_json::from_slice(&err.to_body(ErrorFormat::Hypi)).unwrap();
+    assert_eq!(hypi["message"], "m");
+    assert_eq!(ErrorFormat::Problem.content_type(), APPLICATION_PROBLEM_JSON_HDR);
+}
@@ -0,0 +1,57 @@
+use bytes::Bytes;
+use rapid_utils::err::{HttpError, PipelineError, RapidScriptError};
+use rapid_utils::redact::{RedactionMode, RedactionPolicy, REDACTED_MESSAGE};
+use rapid_utils::wellknown::{CODE_PIPELINE_PLUGIN_BEHAVIOUR, GRPC_ERRS};
+
+fn syntax_err() -> HttpError {
+    PipelineError::RapidScript(RapidScriptError::SyntaxErr {
+        msg: "Unexpected token".to_string(),
+        pos: 7,
+        exp: "secret + 1".to_string(),
+    })
+    .into()
+}
+
+#[test]
+fn default_policy_hides_script() {
+    let err = RedactionPolicy::default().redact(&syntax_err());
+    assert_eq!(err.message, "Unexpected token");
+    assert!(err.context_value("script").is_none());
+    assert!(err.context_value("column").is_some());
+}
+
+#[test]
+fn default_policy_hides_plugin_status_messages() {
+    let status = tonic::Status::internal("connection to 10.0.0.3 refused");
+    let err: HttpError = PipelineError::PluginStatusErr(status).into();
+    let redacted = RedactionPolicy::default().redact(&err);
+    assert_eq!(redacted.message, REDACTED_MESSAGE);
+    assert_eq!(redacted.code.name, GRPC_ERRS.get(&tonic::Code::Internal).unwrap().name);
+    //the original is untouched for logging
+    assert!(err.message.contains("10.0.0.3"));
+}
+
+#[test]
+fn debug_mode_redacts_nothing() {
+    let mut policy = RedactionPolicy::default();
+    policy.mode = RedactionMode::Debug;
+    let err = policy.redact(&syntax_err());
+    assert_eq!(err.context_str("script"), Some("secret + 1"));
+}
+
+#[test]
+fn custom_policy() {
+    let policy = RedactionPolicy::new(RedactionMode::Production)
+        .hide_context_key("plugin")
+        .hide_message(&CODE_PIPELINE_PLUGIN_BEHAVIOUR, "Plugin failed.");
+    let err: HttpError = PipelineError::PluginErr("bad id 42".to_string(), "p1".to_string()).into();
+    let err = policy.redact(&err);
+    assert_eq!(err.message, "Plugin failed.");
+    assert!(err.context.is_none());
+}
+
+#[test]
+fn response_body_is_redacted() {
+    let body = Bytes::from(&syntax_err());
+    assert!(!String::from_utf8_lossy(&body).contains("secret"));
+}
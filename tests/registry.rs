@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use http::StatusCode;
+use rapid_utils::err::ErrorCode;
+use rapid_utils::registry::{self, ErrorCodeRegistry, RegistryError};
+use rapid_utils::wellknown::{error_codes, CODE_MISSING_HOST};
+
+#[test]
+fn well_known_codes_are_unique() {
+    let mut seen = HashMap::new();
+    for code in error_codes() {
+        if let Some(status) = seen.insert(code.name.clone(), code.http_status) {
+            panic!("{} is defined more than once ({} and {})", code.name, status, code.http_status);
+        }
+    }
+    assert_eq!(ErrorCodeRegistry::with_well_known().len(), seen.len());
+}
+
+#[test]
+fn duplicates_are_rejected() {
+    let mut registry = ErrorCodeRegistry::with_well_known();
+    let err = registry
+        .register(ErrorCode::new("hypi_missing_host", StatusCode::NOT_FOUND))
+        .unwrap_err();
+    assert!(matches!(err, RegistryError::Duplicate { existing: 400, .. }));
+}
+
+#[test]
+fn downstream_codes_can_be_registered_and_looked_up() {
+    registry::register(ErrorCode::new("my_service_quota_exceeded", StatusCode::TOO_MANY_REQUESTS)).unwrap();
+    assert_eq!(registry::lookup("my_service_quota_exceeded").unwrap().http_status, 429);
+    assert_eq!(registry::lookup("hypi_missing_host").unwrap().name, CODE_MISSING_HOST.name);
+    assert!(registry::lookup("nope").is_none());
+}
@@ -0,0 +1,55 @@
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+mod common;
+
+use common::Echo;
+use rapid_utils::err::HttpError;
+use rapid_utils::plugin::rapid_plugin_client::RapidPluginClient;
+use rapid_utils::plugin::{output_sequence, InputSequence};
+use rapid_utils::sdk::serve;
+use rapid_utils::wellknown::{CODE_PIPELINE_PLUGIN_BEHAVIOUR, CODE_PIPELINE_NOT_FOUND};
+use tokio_stream::StreamExt;
+
+///How long a test waits for the plugin before failing rather than hanging the suite
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+#[tokio::test]
+async fn serve_fills_sequence_ids() {
+    let addr = free_addr();
+    tokio::spawn(serve(Echo, addr));
+    let connect = async {
+        loop {
+            match RapidPluginClient::connect(format!("http://{}", addr)).await {
+                Ok(client) => break client,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    };
+    let mut client = tokio::time::timeout(TIMEOUT, connect).await.expect("plugin server didn't start");
+    let inputs = [(7, "hello"), (8, "missing"), (9, "panic")]
+        .map(|(id, body)| InputSequence { id, body: body.as_bytes().to_vec(), ..Default::default() });
+    let mut outputs = client.execute(tokio_stream::iter(inputs)).await.unwrap().into_inner();
+    let mut seen = vec![];
+    while let Some(output) = tokio::time::timeout(TIMEOUT, outputs.next()).await.expect("plugin didn't respond") {
+        let output = output.unwrap();
+        let id = output.id;
+        match output.value.clone().unwrap() {
+            output_sequence::Value::Response(res) => assert_eq!((id, res.body.as_slice()), (7, b"hello".as_slice())),
+            output_sequence::Value::Error(_) => {
+                let code = HttpError::try_from(output).unwrap().code;
+                assert_eq!(code, if id == 8 { CODE_PIPELINE_NOT_FOUND } else { CODE_PIPELINE_PLUGIN_BEHAVIOUR });
+            }
+        }
+        seen.push(id);
+        if seen.len() == 3 {
+            break;
+        }
+    }
+    seen.sort();
+    assert_eq!(seen, vec![7, 8, 9]);
+}
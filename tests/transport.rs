@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+mod common;
+
+use common::Echo;
+use rapid_utils::client::PluginClient;
+use rapid_utils::err::{HttpError, PipelineError};
+use rapid_utils::plugin::{output_sequence, InputSequence, OutputSequence};
+use rapid_utils::sdk::PluginService;
+use rapid_utils::transport::{in_memory, PluginAddr, TransportError};
+use rapid_utils::wellknown::{CODE_PIPELINE_NOT_FOUND, CODE_PIPELINE_PLUGIN_TRANSPORT};
+use tokio_stream::StreamExt;
+
+///How long a test waits for the plugin before failing rather than hanging the suite
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn in_memory_round_trip() {
+    let mut client = in_memory(PluginService::new(Echo));
+    let inputs = [(1, "hello"), (2, "missing")]
+        .map(|(id, body)| InputSequence { id, body: body.as_bytes().to_vec(), ..Default::default() });
+    let outputs: Vec<OutputSequence> = client
+        .execute(tokio_stream::iter(inputs))
+        .await
+        .unwrap()
+        .into_inner()
+        .take(2)
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    for output in outputs {
+        match (output.id, output.value.clone()) {
+            (1, Some(output_sequence::Value::Response(res))) => assert_eq!(res.body, b"hello"),
+            (2, Some(output_sequence::Value::Error(_))) => {
+                let err = HttpError::try_from(output).unwrap();
+                assert_eq!(err.code, CODE_PIPELINE_NOT_FOUND);
+                assert_eq!(err.message, "No such pipeline");
+                assert_eq!(err.context_value("id"), Some(&serde_json::json!(1)));
+            }
+            other => panic!("unexpected output {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn in_memory_clients_share_a_server() {
+    let client = in_memory(PluginService::new(Echo));
+    for body in ["a", "b"] {
+        let mut client = client.clone();
+        let input = InputSequence { id: 3, body: body.as_bytes().to_vec(), ..Default::default() };
+        let mut outputs = client.execute(tokio_stream::iter([input])).await.unwrap().into_inner();
+        let output = outputs.next().await.unwrap().unwrap();
+        assert_eq!(output.id, 3);
+    }
+}
+
+#[test]
+fn plugin_addr_from_url() {
+    assert_eq!("http://10.0.0.2:8080".parse::<PluginAddr>().unwrap(), PluginAddr::Tcp("http://10.0.0.2:8080".to_string()));
+    assert_eq!("unix:///run/plugin.sock".parse::<PluginAddr>().unwrap(), PluginAddr::Unix("/run/plugin.sock".into()));
+    assert_eq!("unix:plugin.sock".parse::<PluginAddr>().unwrap(), PluginAddr::Unix("plugin.sock".into()));
+    assert_eq!(
+        "stdio:///usr/bin/my%20plugin?arg=--name&arg=a%26b".parse::<PluginAddr>().unwrap(),
+        PluginAddr::Stdio { program: "/usr/bin/my plugin".into(), args: vec!["--name".to_string(), "a&b".to_string()] }
+    );
+    for unsupported in ["ftp://host", "https://10.0.0.2:8443"] {
+        assert!(matches!(unsupported.parse::<PluginAddr>(), Err(TransportError::UnsupportedScheme(_))), "{}", unsupported);
+    }
+    for invalid in ["no-scheme", "unix://", "stdio://", "stdio:///bin/x?flag", "stdio:///bin/x?arg=%zz"] {
+        assert!(matches!(invalid.parse::<PluginAddr>(), Err(TransportError::InvalidUrl(_))), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn transport_errors_have_their_own_code() {
+    let missing = std::env::temp_dir().join("rapid-utils-missing-plugin");
+    let err = match PluginClient::connect("missing", &format!("stdio://{}", missing.display())).await {
+        Err(e @ PipelineError::PluginTransportErr(_)) => HttpError::from(e),
+        other => panic!("expected a transport error, got {:?}", other.map(|_| ())),
+    };
+    assert_eq!(err.code, CODE_PIPELINE_PLUGIN_TRANSPORT);
+    assert_eq!(err.redacted().message, "Internal error.");
+}
+
+#[tokio::test]
+async fn stdio_transport() {
+    let url = format!("stdio://{}?arg=stdio", env!("CARGO_BIN_EXE_echo-plugin"));
+    let connect = PluginClient::connect("echo", &url);
+    let client = tokio::time::timeout(TIMEOUT, connect).await.expect("plugin didn't start").unwrap();
+    let input = InputSequence { body: b"over stdio".to_vec(), ..Default::default() };
+    let response = tokio::time::timeout(TIMEOUT, client.execute(input)).await.expect("plugin didn't respond");
+    assert_eq!(response.unwrap().body, b"over stdio");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_transport() {
+    let path = std::env::temp_dir().join(format!("rapid-utils-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    tokio::spawn(rapid_utils::sdk::serve_unix(Echo, path.clone()));
+    let connect = async {
+        loop {
+            match PluginClient::connect("echo", &format!("unix://{}", path.display())).await {
+                Ok(client) => break client,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    };
+    let client = tokio::time::timeout(TIMEOUT, connect).await.expect("plugin server didn't start");
+    let input = InputSequence { body: b"over uds".to_vec(), ..Default::default() };
+    assert_eq!(client.execute(input).await.unwrap().body, b"over uds");
+    let _ = std::fs::remove_file(&path);
+}